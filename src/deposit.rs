@@ -0,0 +1,253 @@
+use std::fs;
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use anyhow::{anyhow, Result};
+use crate::output::Rezzy;
+use crate::network::Network;
+use crate::validator::{gwei_to_eth, short_key};
+
+// a full deposit, and the most a compounding(0x02) validator can be topped up to in one deposit
+static MIN_DEPOSIT_GWEI: u64 = 1_000_000_000;
+static FULL_DEPOSIT_GWEI: u64 = 32_000_000_000;
+static MAX_COMPOUNDING_GWEI: u64 = 2_048_000_000_000;
+
+// one entry of a deposit_data-*.json written by the staking deposit cli
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct DepositData {
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub amount: u64,
+    pub signature: String,
+    pub deposit_message_root: String,
+    pub deposit_data_root: String,
+    pub fork_version: String,
+    pub network_name: Option<String>,
+    pub deposit_cli_version: Option<String>,
+}
+
+// where a validator's withdrawals go, from the first byte of its withdrawal credentials
+#[derive(Debug, PartialEq, Clone)]
+pub enum Withdrawal {
+    Bls,
+    Execution(String),
+    Compounding(String),
+}
+
+impl Withdrawal {
+    pub fn parse(credentials: &str) -> Result<Withdrawal> {
+        let wc = decode(credentials, 32)?;
+        let address = || -> Result<String> {
+            if wc[1..12].iter().any(|b| *b != 0) {
+                return Err(anyhow!("bytes 1-11 must be zero before the execution address"));
+            }
+            Ok(format!("0x{}", hex::encode(&wc[12..])))
+        };
+        match wc[0] {
+            0x00 => Ok(Withdrawal::Bls),
+            0x01 => Ok(Withdrawal::Execution(address()?)),
+            0x02 => Ok(Withdrawal::Compounding(address()?)),
+            p => Err(anyhow!("unknown withdrawal prefix 0x{:02x}", p)),
+        }
+    }
+    pub fn address(&self) -> Option<&str> {
+        match self {
+            Withdrawal::Bls => None,
+            Withdrawal::Execution(a) | Withdrawal::Compounding(a) => Some(a.as_str()),
+        }
+    }
+}
+
+fn decode(s: &str, len: usize) -> Result<Vec<u8>> {
+    let b = hex::decode(s.trim_start_matches("0x"))?;
+    if b.len() != len {
+        return Err(anyhow!("expected {} bytes, found {}", len, b.len()));
+    }
+    Ok(b)
+}
+
+fn hash(a: &[u8], b: &[u8]) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update(a);
+    h.update(b);
+    h.finalize().into()
+}
+
+// SSZ hash_tree_root of a fixed size byte vector: 32 byte chunks, zero padded, merkleized
+fn bytes_root(b: &[u8]) -> [u8; 32] {
+    let mut chunks: Vec<[u8; 32]> = b.chunks(32).map(|c| {
+        let mut chunk = [0u8; 32];
+        chunk[..c.len()].copy_from_slice(c);
+        chunk
+    }).collect();
+    merkleize(&mut chunks)
+}
+
+fn merkleize(chunks: &mut Vec<[u8; 32]>) -> [u8; 32] {
+    chunks.resize(chunks.len().next_power_of_two(), [0u8; 32]);
+    while chunks.len() > 1 {
+        *chunks = chunks.chunks(2).map(|p| hash(&p[0], &p[1])).collect();
+    }
+    chunks[0]
+}
+
+fn uint64_root(n: u64) -> [u8; 32] {
+    let mut chunk = [0u8; 32];
+    chunk[..8].copy_from_slice(&n.to_le_bytes());
+    chunk
+}
+
+impl DepositData {
+    // hash_tree_root(DepositMessage{pubkey, withdrawal_credentials, amount})
+    pub fn message_root(&self) -> Result<[u8; 32]> {
+        let mut fields = vec![
+            bytes_root(&decode(self.pubkey.as_str(), 48)?),
+            bytes_root(&decode(self.withdrawal_credentials.as_str(), 32)?),
+            uint64_root(self.amount),
+        ];
+        Ok(merkleize(&mut fields))
+    }
+    // hash_tree_root(DepositData{pubkey, withdrawal_credentials, amount, signature}), what the deposit contract checks
+    pub fn data_root(&self) -> Result<[u8; 32]> {
+        let mut fields = vec![
+            bytes_root(&decode(self.pubkey.as_str(), 48)?),
+            bytes_root(&decode(self.withdrawal_credentials.as_str(), 32)?),
+            uint64_root(self.amount),
+            bytes_root(&decode(self.signature.as_str(), 96)?),
+        ];
+        Ok(merkleize(&mut fields))
+    }
+    // everything short of the BLS signature that makes this deposit unsafe to send on `network`
+    pub fn errors(&self, network: &Network) -> Vec<String> {
+        let mut errs = Vec::new();
+        let root_cmp = |name: &str, computed: Result<[u8; 32]>, listed: &str| match computed {
+            Ok(r) if hex::encode(r) == listed.trim_start_matches("0x").to_lowercase() => None,
+            Ok(r) => Some(format!("{} {} does NOT match the recomputed 0x{}", name, listed, hex::encode(r))),
+            Err(e) => Some(format!("{} can't be recomputed: {}", name, e)),
+        };
+        errs.extend(root_cmp("deposit_message_root", self.message_root(), self.deposit_message_root.as_str()));
+        errs.extend(root_cmp("deposit_data_root", self.data_root(), self.deposit_data_root.as_str()));
+
+        let fork = self.fork_version.trim_start_matches("0x").to_lowercase();
+        if fork != network.genesis_fork_version.trim_start_matches("0x").to_lowercase() {
+            errs.push(format!("fork_version {} is not {}'s genesis fork version({}), the deposit would be for another network", self.fork_version, network.name, network.genesis_fork_version));
+        }
+        if let Some(name) = &self.network_name {
+            if !name.eq_ignore_ascii_case(network.name.as_str()) {
+                errs.push(format!("network_name is {}, not {}", name, network.name));
+            }
+        }
+
+        let withdrawal = Withdrawal::parse(self.withdrawal_credentials.as_str());
+        if let Err(e) = &withdrawal {
+            errs.push(format!("withdrawal_credentials {} are invalid: {}", self.withdrawal_credentials, e));
+        }
+        let max = match withdrawal {
+            Ok(Withdrawal::Compounding(_)) => MAX_COMPOUNDING_GWEI,
+            _ => FULL_DEPOSIT_GWEI,
+        };
+        if self.amount < MIN_DEPOSIT_GWEI || self.amount > max {
+            errs.push(format!("amount is {}, deposits must be between {} and {}", gwei_to_eth(self.amount), gwei_to_eth(MIN_DEPOSIT_GWEI), gwei_to_eth(max)));
+        }
+        errs
+    }
+}
+
+pub fn load_deposits(path: &str) -> Result<Vec<DepositData>> {
+    let contents = fs::read_to_string(path)?;
+    let deposits: Vec<DepositData> = serde_json::from_str(contents.as_str())?;
+    Ok(deposits)
+}
+
+// `valid8r deposit-data`: offline checks of a deposit file before any ETH is sent, false when a deposit is unsafe
+pub fn deposit_data_check(path: &str, network: &Network) -> bool {
+    let banner = Rezzy{ message: format!("Deposit Data: {}", path) };
+    banner.bold();
+
+    let deposits = match load_deposits(path) {
+        Ok(d) => d,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("{} is NOT a deposit data file: {}", path, e) };
+            msg.write_red();
+            return false;
+        },
+    };
+    if deposits.is_empty() {
+        let msg = Rezzy{ message: format!("{} has no deposits", path) };
+        msg.write_yellow();
+        return true;
+    }
+
+    let mut valid = 0;
+    let mut pubkeys: BTreeMap<String, usize> = BTreeMap::new();
+    for d in &deposits {
+        let key = format!("0x{}", d.pubkey.trim_start_matches("0x").to_lowercase());
+        *pubkeys.entry(key.clone()).or_default() += 1;
+
+        let errs = d.errors(network);
+        for e in &errs {
+            let msg = Rezzy{ message: format!("validator {}: {}", short_key(key.as_str()), e) };
+            msg.write_red();
+        }
+        if errs.is_empty() {
+            valid += 1;
+        }
+        match Withdrawal::parse(d.withdrawal_credentials.as_str()) {
+            Ok(Withdrawal::Bls) => {
+                let msg = Rezzy{ message: format!("validator {} has BLS(0x00) withdrawal credentials, it can't withdraw until they're changed to an execution address", short_key(key.as_str())) };
+                msg.write_yellow();
+            },
+            Ok(w) => println!("{}: {}, withdrawals to {}", key, gwei_to_eth(d.amount), w.address().unwrap_or_default()),
+            Err(_) => (),
+        }
+    }
+
+    for (key, n) in pubkeys.iter().filter(|(_, n)| **n > 1) {
+        let msg = Rezzy{ message: format!("validator {} is deposited {} times in this file", short_key(key.as_str()), n) };
+        msg.write_yellow();
+    }
+
+    let msg = Rezzy{ message: format!("{} of {} deposits have matching roots, fork version and withdrawal credentials for {}(signatures are not verified)", valid, deposits.len(), network.name) };
+    if valid == deposits.len() {
+        msg.write_green();
+    } else {
+        msg.write_red();
+    }
+    valid == deposits.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposit_roots_and_credentials() {
+        // a deposit_data-*.json entry whose roots were computed outside this crate from the spec's DepositMessage/DepositData
+        let mut deposits: Vec<DepositData> = serde_json::from_str(r#"[{
+            "pubkey": "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f",
+            "withdrawal_credentials": "010000000000000000000000a0a1a2a3a4a5a6a7a8a9aaabacadaeafb0b1b2b3",
+            "amount": 32000000000,
+            "signature": "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f",
+            "deposit_message_root": "8cd9b66d1591adc54613f048ee442c17f98376f0e1f686baeae57771ca34bd37",
+            "deposit_data_root": "95002b7709f7e07b8e2ebcf3ff70e105c2314de8ef3c135cfc94204a560c9226",
+            "fork_version": "00000000",
+            "network_name": "mainnet",
+            "deposit_cli_version": "2.7.0"
+        }]"#).unwrap();
+        let d = deposits.remove(0);
+        assert!(d.errors(&Network::mainnet()).is_empty());
+
+        let errs = d.errors(&Network::hoodi());
+        assert_eq!(errs.len(), 2);
+        assert!(errs[0].contains("fork_version") && errs[1].contains("network_name"));
+
+        let tampered = DepositData{ amount: 31_000_000_000, ..d.clone() };
+        let errs = tampered.errors(&Network::mainnet());
+        assert_eq!(errs.len(), 2);
+        assert!(errs.iter().all(|e| e.contains("root")));
+
+        assert_eq!(Withdrawal::parse(d.withdrawal_credentials.as_str()).unwrap(), Withdrawal::Execution(String::from("0xa0a1a2a3a4a5a6a7a8a9aaabacadaeafb0b1b2b3")));
+        assert_eq!(Withdrawal::parse(&"00".repeat(32)).unwrap(), Withdrawal::Bls);
+        assert!(Withdrawal::parse(&format!("02{}", "11".repeat(31))).is_err());
+    }
+}
@@ -0,0 +1,453 @@
+use std::fs;
+use std::path::Path;
+use sysinfo::{System, SystemExt, ProcessExt};
+use anyhow::{anyhow, Result};
+use serde_yaml::Value;
+use crate::output::Rezzy;
+
+// command line flags clients use to point at their data directory
+static DATADIR_FLAGS: [&str; 4] = ["--datadir", "--data-dir", "--data-path", "--data-base-path"];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PortMapping {
+    pub host_port: u16,
+    pub container_port: u16,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ComposeService {
+    pub name: String,
+    pub image: String,
+    pub network_mode: Option<String>,
+    pub restart: Option<String>,
+    pub ports: Vec<PortMapping>,
+    pub volumes: Vec<String>,
+    // host source and container target of each bind mount
+    pub mounts: Vec<(String, String)>,
+    pub command: Vec<String>,
+}
+
+impl ComposeService {
+    pub fn host_network(&self) -> bool {
+        self.network_mode.as_deref() == Some("host")
+    }
+    pub fn host_port(&self, container_port: u16) -> Option<u16> {
+        if self.host_network() {
+            return Some(container_port);
+        }
+        self.ports.iter()
+            .find(|p| p.container_port == container_port)
+            .map(|p| p.host_port)
+    }
+    pub fn container_port(&self, host_port: u16) -> u16 {
+        if self.host_network() {
+            return host_port;
+        }
+        self.ports.iter()
+            .find(|p| p.host_port == host_port)
+            .map(|p| p.container_port)
+            .unwrap_or(host_port)
+    }
+    // where a path inside the container lives on the host, when a bind mount covers it
+    pub fn host_path(&self, path: &str) -> Option<String> {
+        self.mounts.iter()
+            .filter(|(_, target)| path == target || path.starts_with(&format!("{}/", target.trim_end_matches('/'))))
+            .max_by_key(|(_, target)| target.len())
+            .map(|(source, target)| format!("{}{}", source.trim_end_matches('/'), &path[target.trim_end_matches('/').len()..]))
+    }
+    pub fn datadir(&self) -> Option<String> {
+        flag_value(&self.command, &DATADIR_FLAGS)
+    }
+    // Some(true) when the container comes back after a reboot, Some(false) when only after a crash
+    pub fn restarts_on_boot(&self) -> Option<bool> {
+        match self.restart.as_deref() {
+            Some("always") | Some("unless-stopped") | Some("any") => Some(true),
+            Some(r) if r.starts_with("on-failure") => Some(false),
+            _ => None,
+        }
+    }
+    pub fn is_mounted(&self, path: &str) -> bool {
+        self.volumes.iter()
+            .map(|v| v.trim_end_matches('/'))
+            .any(|v| path.trim_end_matches('/') == v || path.starts_with(&format!("{}/", v)))
+    }
+    fn is_validator(&self) -> bool {
+        let name = self.name.to_lowercase();
+        let image = self.image.to_lowercase();
+        name.contains("validator") || image.contains("validator")
+            || self.command.iter().take(2).any(|c| c == "vc" || c == "validator")
+    }
+}
+
+// value of the first of `flags` found in a command line, as `--flag value` or `--flag=value`
+pub fn flag_value(args: &[String], flags: &[&str]) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        for flag in flags.iter() {
+            if arg == flag {
+                return args.next().cloned();
+            }
+            if let Some(val) = arg.strip_prefix(&format!("{}=", flag)) {
+                return Some(String::from(val));
+            }
+        }
+    }
+    None
+}
+
+// substrings identifying a client in image names, service names and process command lines
+fn client_keywords(client: &str) -> &'static [&'static str] {
+    match client {
+        "GETH" => &["geth", "client-go"],
+        "BESU" => &["besu"],
+        "NETHERMIND" => &["nethermind"],
+        "OPENETHEREUM" => &["openethereum"],
+        "LIGHTHOUSE" => &["lighthouse"],
+        "PRYSM" => &["prysm", "beacon-chain"],
+        "TEKU" => &["teku"],
+        "NIMBUS" => &["nimbus"],
+        _ => &[],
+    }
+}
+
+fn yaml_str(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_port(v: &Value) -> Option<PortMapping> {
+    if let Value::Mapping(_) = v {
+        let container_port = yaml_str(&v["target"])?.parse().ok()?;
+        let host_port = match yaml_str(&v["published"]) {
+            Some(p) => p.parse().ok()?,
+            None => container_port,
+        };
+        return Some(PortMapping{ host_port, container_port });
+    }
+    // short syntax: [IP:]HOST:CONTAINER[/proto] or CONTAINER
+    let spec = yaml_str(v)?;
+    let spec = spec.split('/').next()?;
+    let parts: Vec<&str> = spec.rsplitn(3, ':').collect();
+    let container_port: u16 = parts[0].parse().ok()?;
+    let host_port = match parts.get(1) {
+        Some(p) => p.parse().ok()?,
+        None => container_port,
+    };
+    Some(PortMapping{ host_port, container_port })
+}
+
+// source(if any) and target of a volume
+fn parse_volume(v: &Value) -> Option<(Option<String>, String)> {
+    if let Value::Mapping(_) = v {
+        return Some((yaml_str(&v["source"]), yaml_str(&v["target"])?));
+    }
+    // short syntax: [SOURCE:]TARGET[:MODE]
+    let spec = yaml_str(v)?;
+    let parts: Vec<&str> = spec.split(':').collect();
+    match parts.len() {
+        1 => Some((None, String::from(parts[0]))),
+        _ => Some((Some(String::from(parts[0])), String::from(parts[1]))),
+    }
+}
+
+// bind mounts name a host path, named volumes don't
+fn is_bind(source: &str) -> bool {
+    source.starts_with('/') || source.starts_with('.') || source.starts_with('~')
+}
+
+pub fn parse_compose(contents: &str) -> Result<Vec<ComposeService>> {
+    let doc: Value = serde_yaml::from_str(contents)?;
+    let services = match doc["services"].as_mapping() {
+        Some(s) => s,
+        None => return Err(anyhow!("compose file has no services section")),
+    };
+
+    let mut out = Vec::new();
+    for (name, svc) in services {
+        let command = match &svc["command"] {
+            Value::String(s) => s.split_whitespace().map(String::from).collect(),
+            Value::Sequence(seq) => seq.iter().filter_map(yaml_str).collect(),
+            _ => Vec::new(),
+        };
+        let volumes: Vec<(Option<String>, String)> = svc["volumes"].as_sequence()
+            .map(|s| s.iter().filter_map(parse_volume).collect())
+            .unwrap_or_default();
+        let restart = yaml_str(&svc["restart"])
+            .or_else(|| yaml_str(&svc["deploy"]["restart_policy"]["condition"]));
+        out.push(ComposeService{
+            name: yaml_str(name).unwrap_or_default(),
+            image: yaml_str(&svc["image"]).unwrap_or_default(),
+            network_mode: yaml_str(&svc["network_mode"]),
+            restart,
+            ports: svc["ports"].as_sequence().map(|s| s.iter().filter_map(parse_port).collect()).unwrap_or_default(),
+            mounts: volumes.iter()
+                .filter_map(|(source, target)| Some((source.clone().filter(|s| is_bind(s))?, target.clone())))
+                .collect(),
+            volumes: volumes.into_iter().map(|(_, target)| target).collect(),
+            command,
+        });
+    }
+    Ok(out)
+}
+
+pub fn load_compose(path: &str) -> Result<Vec<ComposeService>> {
+    let contents = fs::read_to_string(path)?;
+    let mut services = parse_compose(contents.as_str())?;
+    // relative bind mounts are relative to the compose file
+    let dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    for svc in services.iter_mut() {
+        for (source, _) in svc.mounts.iter_mut() {
+            if source.starts_with('.') {
+                *source = dir.join(source.as_str()).to_string_lossy().into_owned();
+            }
+        }
+    }
+    Ok(services)
+}
+
+pub fn find_service(services: &[ComposeService], client: &str) -> Option<ComposeService> {
+    let keys = client_keywords(client);
+    services.iter()
+        .filter(|s| !s.is_validator())
+        .find(|s| {
+            let name = s.name.to_lowercase();
+            let image = s.image.to_lowercase();
+            keys.iter().any(|k| name.contains(k) || image.contains(k))
+        })
+        .cloned()
+}
+
+pub fn addr_port(addr: &str) -> Option<u16> {
+    addr.rsplit_once(':').and_then(|(_, p)| p.parse().ok())
+}
+
+// rewrite a host:port address so it points at the host port docker publishes for it
+pub fn remap_addr(addr: &str, svc: &ComposeService) -> String {
+    match addr_port(addr).and_then(|p| svc.host_port(p)) {
+        Some(p) => format!("{}:{}", addr.rsplit_once(':').unwrap().0, p),
+        None => String::from(addr),
+    }
+}
+
+pub fn parse_cgroup(contents: &str) -> Option<String> {
+    for line in contents.lines() {
+        let path = match line.splitn(3, ':').nth(2) {
+            Some(p) => p,
+            None => continue,
+        };
+        if !["docker", "containerd", "libpod", "kubepods"].iter().any(|r| path.contains(r)) {
+            continue;
+        }
+        // container ids are 64 hex chars, wrapped in e.g. /docker/<id> or docker-<id>.scope
+        for seg in path.split(|c: char| !c.is_ascii_hexdigit()) {
+            if seg.len() == 64 {
+                return Some(String::from(seg));
+            }
+        }
+    }
+    None
+}
+
+pub fn container_id(pid: i32) -> Option<String> {
+    let contents = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    parse_cgroup(contents.as_str())
+}
+
+// pid and command line of every running process that looks like `client`
+pub fn client_processes(client: &str) -> Vec<(i32, Vec<String>)> {
+    processes_matching(client_keywords(client))
+}
+
+// pid and command line of every process whose name(or runtime command line) contains one of `keys`
+pub fn processes_matching(keys: &[&str]) -> Vec<(i32, Vec<String>)> {
+    let me = std::process::id() as i32;
+    let sys = System::new_all();
+
+    let mut procs: Vec<(i32, Vec<String>)> = sys.get_processes().iter()
+        .filter(|(pid, p)| **pid != me && !p.name().contains("valid8r"))
+        .filter(|(_, p)| {
+            // besu and teku run under java, lodestar under node, so only look past the process name for runtimes
+            let name = p.name().to_lowercase();
+            let cmd = match name.as_str() {
+                "java" | "dotnet" | "node" => p.cmd().join(" ").to_lowercase(),
+                _ => String::new(),
+            };
+            keys.iter().any(|k| name.contains(k) || cmd.contains(k))
+        })
+        .map(|(pid, p)| (*pid, p.cmd().to_vec()))
+        .collect();
+    procs.sort_unstable();
+    procs
+}
+
+pub fn client_pids(client: &str) -> Vec<i32> {
+    client_processes(client).into_iter().map(|(pid, _)| pid).collect()
+}
+
+pub fn docker_check(client: &str, service: Option<&ComposeService>, expected_ports: &[u16]) {
+    let pids = client_pids(client);
+    let containers: Vec<String> = pids.iter().filter_map(|p| container_id(*p)).collect();
+
+    if pids.is_empty() {
+        let msg = Rezzy{ message: format!("Could not find a running {} process", client) };
+        msg.write_yellow();
+    } else if let Some(id) = containers.first() {
+        let msg = Rezzy{ message: format!("{} is running in container {}", client, &id[..12]) };
+        msg.write_green();
+        if service.is_none() {
+            let msg = Rezzy{ message: format!("{} runs in a container but no compose service was found for it, published ports can't be mapped(use --compose-file)", client) };
+            msg.write_yellow();
+        }
+    } else {
+        let msg = Rezzy{ message: format!("{} is running directly on the host", client) };
+        msg.write_green();
+    }
+
+    let svc = match service {
+        Some(s) => s,
+        None => return,
+    };
+
+    // restart policy
+    match svc.restarts_on_boot() {
+        Some(true) => {
+            let msg = Rezzy{ message: format!("{} compose service {} restart policy: {}", client, svc.name, svc.restart.as_ref().unwrap()) };
+            msg.write_green();
+        },
+        Some(false) => {
+            let msg = Rezzy{ message: format!("{} compose service {} restart policy {} will not restart after a reboot(prefer unless-stopped)", client, svc.name, svc.restart.as_ref().unwrap()) };
+            msg.write_yellow();
+        },
+        None => {
+            let msg = Rezzy{ message: format!("{} compose service {} has NO restart policy(set restart: unless-stopped)", client, svc.name) };
+            msg.write_red();
+        },
+    }
+
+    // published ports
+    for port in expected_ports {
+        if svc.host_network() {
+            let msg = Rezzy{ message: format!("{} compose service {} uses host networking, port {} is not remapped", client, svc.name, port) };
+            msg.write_green();
+            continue;
+        }
+        match svc.host_port(*port) {
+            Some(p) if p == *port => {
+                let msg = Rezzy{ message: format!("{} container port {} is published on host port {}", client, port, p) };
+                msg.write_green();
+            },
+            Some(p) => {
+                let msg = Rezzy{ message: format!("{} container port {} is remapped, checking host port {} instead", client, port, p) };
+                msg.write_yellow();
+            },
+            None => {
+                let msg = Rezzy{ message: format!("{} container port {} is NOT published by compose service {}", client, port, svc.name) };
+                msg.write_red();
+            },
+        }
+    }
+
+    // datadir volume
+    match svc.datadir() {
+        Some(dir) => {
+            if svc.is_mounted(&dir) {
+                let msg = Rezzy{ message: format!("{} datadir {} is on a mounted volume", client, dir) };
+                msg.write_green();
+            } else {
+                let msg = Rezzy{ message: format!("{} datadir {} is NOT on a mounted volume, chain data is lost when the container is recreated", client, dir) };
+                msg.write_red();
+            }
+        },
+        None => {
+            if svc.volumes.is_empty() {
+                let msg = Rezzy{ message: format!("{} compose service {} has NO volumes, chain data is lost when the container is recreated", client, svc.name) };
+                msg.write_red();
+            } else {
+                let msg = Rezzy{ message: format!("{} compose service {} mounts volumes: {}", client, svc.name, svc.volumes.join(", ")) };
+                msg.write_green();
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_container_ids() {
+        let v1 = "12:memory:/docker/3f4a5b6c7d8e9f00112233445566778899aabbccddeeff00112233445566778a\n";
+        let v2 = "0::/system.slice/docker-3f4a5b6c7d8e9f00112233445566778899aabbccddeeff00112233445566778a.scope\n";
+        let host = "0::/user.slice/user-1000.slice/session-2.scope\n";
+        assert_eq!(parse_cgroup(v1).unwrap().len(), 64);
+        assert_eq!(parse_cgroup(v1), parse_cgroup(v2));
+        assert_eq!(parse_cgroup(host), None);
+    }
+
+    #[test]
+    fn compose_port_mapping() {
+        let compose = r#"
+services:
+  geth:
+    image: ethereum/client-go:stable
+    restart: unless-stopped
+    command: --http --datadir /data
+    ports:
+      - "30313:30303/tcp"
+      - "127.0.0.1:8545:8545"
+      - target: 8551
+        published: 18551
+    volumes:
+      - ./geth:/data
+  lighthouse-vc:
+    image: sigp/lighthouse
+    command: ["lighthouse", "vc"]
+"#;
+        let services = parse_compose(compose).unwrap();
+        let geth = find_service(&services, "GETH").unwrap();
+        assert_eq!(geth.host_port(30303), Some(30313));
+        assert_eq!(geth.host_port(8545), Some(8545));
+        assert_eq!(geth.host_port(8551), Some(18551));
+        assert_eq!(remap_addr("0.0.0.0:30303", &geth), "0.0.0.0:30313");
+        assert_eq!(geth.datadir(), Some(String::from("/data")));
+        assert_eq!(geth.host_path("/data/jwt.hex"), Some(String::from("./geth/jwt.hex")));
+        assert_eq!(geth.host_path("/database"), None);
+        assert_eq!(find_service(&services, "LIGHTHOUSE"), None);
+    }
+
+    #[test]
+    fn compose_restart_and_volumes() {
+        let compose = r#"
+services:
+  geth:
+    image: ethereum/client-go:stable
+    restart: unless-stopped
+    volumes:
+      - ./geth:/data/
+  nethermind:
+    image: nethermind/nethermind
+    restart: on-failure:5
+  besu:
+    image: hyperledger/besu
+    deploy:
+      restart_policy:
+        condition: any
+  openethereum:
+    image: openethereum/openethereum
+"#;
+        let services = parse_compose(compose).unwrap();
+        let restarts = |client| find_service(&services, client).unwrap().restarts_on_boot();
+        assert_eq!(restarts("GETH"), Some(true));
+        assert_eq!(restarts("NETHERMIND"), Some(false));
+        assert_eq!(restarts("BESU"), Some(true));
+        assert_eq!(restarts("OPENETHEREUM"), None);
+        let geth = find_service(&services, "GETH").unwrap();
+        assert!(geth.is_mounted("/data"));
+        assert!(geth.is_mounted("/data/geth/chaindata"));
+        assert!(!geth.is_mounted("/database"));
+        assert!(!find_service(&services, "OPENETHEREUM").unwrap().is_mounted("/data"));
+    }
+}
@@ -0,0 +1,198 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::net::TcpListener;
+use std::io::ErrorKind;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde_json::json;
+use chrono::Utc;
+use anyhow::{anyhow, Result};
+use crate::output::Rezzy;
+use crate::reference::Endpoint;
+use crate::docker::{self, ComposeService};
+
+// flags execution clients(geth, besu, nethermind) and consensus clients(lighthouse, prysm/nimbus/lodestar, teku) take the secret with
+static EL_JWT_FLAGS: [&str; 3] = ["--authrpc.jwtsecret", "--engine-jwt-secret", "--JsonRpc.JwtSecretFile"];
+static CL_JWT_FLAGS: [&str; 3] = ["--execution-jwt", "--jwt-secret", "--ee-jwt-secret-file"];
+
+// methods valid8r asks the execution client about, any post-cancun client serves them
+static ENGINE_CAPABILITIES: [&str; 3] = ["engine_newPayloadV3", "engine_forkchoiceUpdatedV3", "engine_getPayloadV3"];
+
+pub fn parse_secret(contents: &str) -> Result<Vec<u8>> {
+    let secret = contents.trim().trim_start_matches("0x");
+    if secret.len() != 64 {
+        return Err(anyhow!("expected 64 hex characters, found {}", secret.len()));
+    }
+    Ok(hex::decode(secret)?)
+}
+
+// HS256 token with only the iat claim, which is all the engine api requires
+pub fn jwt_token(secret: &[u8], iat: i64) -> Result<String> {
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(json!({ "iat": iat }).to_string());
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)?;
+    mac.update(format!("{}.{}", header, claims).as_bytes());
+    let sig = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+    Ok(format!("{}.{}.{}", header, claims, sig))
+}
+
+// host path of the jwt secret from a running client's command line, falling back to its compose service
+fn jwt_path(client: &str, flags: &[&str], svc: Option<&ComposeService>) -> Option<String> {
+    for (pid, cmd) in docker::client_processes(client) {
+        if let Some(path) = docker::flag_value(&cmd, flags) {
+            return match docker::container_id(pid) {
+                Some(id) => container_path(client, path, &id[..id.len().min(12)], svc),
+                None => Some(path),
+            };
+        }
+    }
+    let s = svc?;
+    let path = docker::flag_value(&s.command, flags)?;
+    container_path(client, path, s.name.as_str(), Some(s))
+}
+
+// a path given to a containerised client only exists on the host where a bind mount puts it
+fn container_path(client: &str, path: String, container: &str, svc: Option<&ComposeService>) -> Option<String> {
+    if let Some(host) = svc.and_then(|s| s.host_path(path.as_str())) {
+        return Some(host);
+    }
+    let msg = Rezzy{ message: format!("{} JWT secret {} is a path inside container {} with no bind mount to the host(use --compose-file or --jwt-secret)", client, path, container) };
+    msg.write_yellow();
+    None
+}
+
+fn secret_check(client: &str, path: &str) -> Option<Vec<u8>> {
+    let meta = match fs::metadata(path) {
+        Ok(m) => m,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("{} JWT secret {} can't be read: {}", client, path, e) };
+            msg.write_red();
+            return None;
+        },
+    };
+
+    let mode = meta.permissions().mode() & 0o777;
+    if mode & 0o007 != 0 {
+        let msg = Rezzy{ message: format!("{} JWT secret {} is WORLD accessible(mode {:o}), chmod 600 it", client, path, mode) };
+        msg.write_red();
+    } else if mode & 0o070 != 0 {
+        let msg = Rezzy{ message: format!("{} JWT secret {} is group accessible(mode {:o}), prefer 600", client, path, mode) };
+        msg.write_yellow();
+    } else {
+        let msg = Rezzy{ message: format!("{} JWT secret {} has restrictive permissions(mode {:o})", client, path, mode) };
+        msg.write_green();
+    }
+
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("{} JWT secret {} can't be read: {}", client, path, e) };
+            msg.write_red();
+            return None;
+        },
+    };
+    match parse_secret(contents.as_str()) {
+        Ok(secret) => {
+            let msg = Rezzy{ message: format!("{} JWT secret {} is a valid 32 byte hex secret", client, path) };
+            msg.write_green();
+            Some(secret)
+        },
+        Err(e) => {
+            let msg = Rezzy{ message: format!("{} JWT secret {} is NOT a 32 byte hex secret: {}", client, path, e) };
+            msg.write_red();
+            None
+        },
+    }
+}
+
+pub fn engine_check(eth1: &str, eth2: &str, engine_addr: &str, jwt_secret: Option<&String>, eth1_svc: Option<&ComposeService>, eth2_svc: Option<&ComposeService>) {
+    match TcpListener::bind(engine_addr) {
+        Ok(_) => {
+            let msg = Rezzy{ message: format!("{} IS NOT LISTENING for the engine API ON PORT: {}", eth1, engine_addr) };
+            msg.write_red();
+        },
+        Err(e) => {
+            if e.kind() == ErrorKind::AddrInUse {
+                let msg = Rezzy{ message: format!("{} is listening for the engine API on port: {}", eth1, engine_addr) };
+                msg.write_green();
+            } else {
+                let msg = Rezzy{ message: format!("{:?} misc error when listening on {}", e, engine_addr) };
+                msg.write_yellow();
+            }
+        }
+    }
+
+    let eth1_path = jwt_secret.cloned().or_else(|| jwt_path(eth1, &EL_JWT_FLAGS, eth1_svc));
+    let eth2_path = jwt_secret.cloned().or_else(|| jwt_path(eth2, &CL_JWT_FLAGS, eth2_svc));
+
+    let mut secrets: Vec<Option<Vec<u8>>> = Vec::new();
+    for (client, path) in [(eth1, &eth1_path), (eth2, &eth2_path)].iter() {
+        match path {
+            // both clients pointing at one file only needs checking once
+            Some(_) if secrets.len() == 1 && eth1_path == eth2_path => secrets.push(secrets[0].clone()),
+            Some(p) => secrets.push(secret_check(client, p.as_str())),
+            None => {
+                let msg = Rezzy{ message: format!("Could not find the JWT secret {} was started with(use --jwt-secret)", client) };
+                msg.write_yellow();
+                secrets.push(None);
+            },
+        }
+    }
+
+    if let (Some(a), Some(b)) = (&secrets[0], &secrets[1]) {
+        if a == b {
+            let msg = Rezzy{ message: format!("{} and {} share the same JWT secret", eth1, eth2) };
+            msg.write_green();
+        } else {
+            let msg = Rezzy{ message: format!("{} and {} are configured with DIFFERENT JWT secrets({:?} vs {:?})", eth1, eth2, eth1_path.unwrap_or_default(), eth2_path.unwrap_or_default()) };
+            msg.write_red();
+        }
+    }
+
+    // authenticate the way the consensus client does, with the secret the execution client serves
+    let secret = match secrets.into_iter().flatten().next() {
+        Some(s) => s,
+        None => return,
+    };
+    let token = match jwt_token(&secret, Utc::now().timestamp()) {
+        Ok(t) => t,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Could not sign an engine API JWT: {}", e) };
+            msg.write_red();
+            return;
+        },
+    };
+    let mut engine = Endpoint::new(format!("http://{}", engine_addr).as_str());
+    engine.headers.insert(String::from("Authorization"), format!("Bearer {}", token));
+
+    match engine.rpc("engine_exchangeCapabilities", json!([ENGINE_CAPABILITIES])) {
+        Ok(caps) => {
+            let n = caps.as_array().map_or(0, |c| c.len());
+            let msg = Rezzy{ message: format!("{} accepted an authenticated engine_exchangeCapabilities call({} engine methods)", eth1, n) };
+            msg.write_green();
+        },
+        Err(e) => {
+            let msg = Rezzy{ message: format!("{} REJECTED an authenticated engine API call: {}", eth1, e) };
+            msg.write_red();
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jwt_secret_and_token() {
+        let secret = parse_secret("0x7365637265747365637265747365637265747365637265747365637265747365\n").unwrap();
+        assert_eq!(secret.len(), 32);
+        assert!(parse_secret("deadbeef").is_err());
+
+        let token = jwt_token(&secret, 1700000000).unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(URL_SAFE_NO_PAD.decode(parts[1]).unwrap(), br#"{"iat":1700000000}"#.to_vec());
+    }
+}
@@ -1,14 +1,29 @@
 extern crate reqwest;
 use serde::{Serialize, Deserialize};
 use serde_json::json;
-use anyhow::Result;
-use crate::output::Rezzy;
+use chrono::Utc;
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use crate::output::{Rezzy, fmt_duration};
+use crate::network::{Network, chain_name};
+use crate::reference::{Endpoint, References};
 
 static GETH_GIT: &str = "https://api.github.com/repos/ethereum/go-ethereum/releases/latest";
 static BESU_GIT: &str = "https://api.github.com/repos/hyperledger/besu/releases/latest";
 static NETHERMIND_GIT: &str = "https://api.github.com/repos/nethermindeth/nethermind/releases/latest";
 static OPENETHEREUM_GIT: &str = "https://api.github.com/repos/openethereum/openethereum/releases/latest";
 
+// head age, in slots, past which the node is considered lagging/stalled
+static HEAD_WARN_SLOTS: u64 = 2;
+static HEAD_FAIL_SLOTS: u64 = 5;
+
+// seconds between the two eth_syncing samples the sync rate is measured over
+pub static SYNC_SAMPLE_SECS: u64 = 5;
+
+// blocks below the common head at which local and reference hashes are compared
+static REORG_DEPTH: u64 = 2;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RpcRequest {
     pub jsonrpc: String,
@@ -25,46 +40,270 @@ pub struct RpcResponse {
     pub result: Option<serde_json::Value>,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SyncStatus {
+    pub starting: u64,
+    pub current: u64,
+    pub highest: u64,
+    pub pulled_states: Option<u64>,
+    pub known_states: Option<u64>,
+}
+
+fn hex_field(v: &serde_json::Value, field: &str) -> Option<u64> {
+    v[field].as_str().and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+}
+
+impl SyncStatus {
+    pub fn parse(v: &serde_json::Value) -> Option<SyncStatus> {
+        Some(SyncStatus{
+            starting: hex_field(v, "startingBlock").unwrap_or(0),
+            current: hex_field(v, "currentBlock")?,
+            highest: hex_field(v, "highestBlock")?,
+            pulled_states: hex_field(v, "pulledStates"),
+            known_states: hex_field(v, "knownStates"),
+        })
+    }
+    pub fn percent(&self) -> f64 {
+        if self.highest <= self.starting {
+            return 100.0;
+        }
+        (self.current.saturating_sub(self.starting)) as f64 * 100.0 / (self.highest - self.starting) as f64
+    }
+    pub fn state_percent(&self) -> Option<f64> {
+        match (self.pulled_states, self.known_states) {
+            (Some(p), Some(k)) if k > 0 => Some(p as f64 * 100.0 / k as f64),
+            _ => None,
+        }
+    }
+    // seconds left at the block rate seen between two samples taken `elapsed` seconds apart
+    pub fn eta(&self, later: &SyncStatus, elapsed: f64) -> Option<u64> {
+        let done = later.current.saturating_sub(self.current);
+        if done == 0 || elapsed <= 0.0 {
+            return None;
+        }
+        let rate = done as f64 / elapsed;
+        Some((later.highest.saturating_sub(later.current) as f64 / rate) as u64)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Eth1Client {
     pub name: String,
     pub http_addr: String,
-    pub infura_addr: String,
-    pub testnet: bool,
+    pub references: References,
+    pub network: Network,
 }
 
 impl Eth1Client {
-    pub fn new(name: String, http_addr: String, infura_addr: String, testnet: bool) -> Eth1Client {
+    pub fn new(name: String, http_addr: String, references: References, network: Network) -> Eth1Client {
         Eth1Client{
             name,
             http_addr,
-            infura_addr,
-            testnet,
+            references,
+            network,
         }
     }
-    pub fn infura_req(&self, st: &str) -> Result<reqwest::blocking::Response> {
-        let req = RpcRequest {
-            jsonrpc: String::from("2.0"),
-            method: String::from(st),
-            params: json!([]),
-            id: String::from("1"),
+    pub fn chain_check(&self) {
+        let chain_id = match eth_result("eth_chainId", json!([]), self.http_addr.as_str()) {
+            Ok(r) => r.as_str().and_then(|v| u64::from_str_radix(v.trim_start_matches("0x"), 16).ok()),
+            Err(_) => None,
         };
-    
-        let serialized = match serde_json::to_string(&req) {
-            Ok(s) => s,
+        let net_version = match eth_result("net_version", json!([]), self.http_addr.as_str()) {
+            Ok(r) => r.as_str().and_then(|v| v.parse::<u64>().ok()),
+            Err(_) => None,
+        };
+
+        let id = match (chain_id, net_version) {
+            (Some(c), Some(n)) => {
+                if c != n {
+                    let msg = Rezzy{ message: format!("{} chain id({}) and network id({}) DISAGREE", self.name, c, n) };
+                    msg.write_red();
+                }
+                c
+            },
+            (Some(c), None) => c,
+            (None, Some(n)) => {
+                let msg = Rezzy{ message: format!("{} did not answer eth_chainId, falling back to network id", self.name) };
+                msg.write_yellow();
+                n
+            },
+            (None, None) => {
+                let msg = Rezzy{ message: format!("Unable to get environment from {}", self.name) };
+                msg.write_red();
+                return;
+            },
+        };
+
+        if id == self.network.chain_id {
+            let msg = Rezzy{ message: format!("{} is on {}(chain id {})", self.name, self.network.name, id) };
+            msg.write_green();
+        } else {
+            let msg = Rezzy{ message: format!("{} is on {}(chain id {}) but {}(chain id {}) was expected", self.name, chain_name(id), id, self.network.name, self.network.chain_id) };
+            msg.write_red();
+        }
+    }
+    pub fn genesis_check(&self) {
+        let block = match eth_result("eth_getBlockByNumber", json!(["0x0", false]), self.http_addr.as_str()) {
+            Ok(b) => b,
             Err(e) => {
-                let msg = Rezzy{ message: format!("Error reading request: {:?}", e) };
+                let msg = Rezzy{ message: format!("Unable to get genesis block from {}: {}", self.name, e) };
                 msg.write_red();
-                String::from("")
+                return;
             },
         };
-    
-        let client = reqwest::blocking::Client::new();
-        let res = client.post(self.http_addr.as_str())
-            .header("Content-Type", "application/json")
-            .body(serialized)
-            .send()?;
-        Ok(res)
+
+        match block["hash"].as_str() {
+            Some(hash) if hash.eq_ignore_ascii_case(self.network.genesis_hash.as_str()) => {
+                let msg = Rezzy{ message: format!("{} genesis block matches {}: {}", self.name, self.network.name, hash) };
+                msg.write_green();
+            },
+            Some(hash) => {
+                let msg = Rezzy{ message: format!("{} genesis block {} does NOT match {} genesis {}(wrong chain or corrupted database)", self.name, hash, self.network.name, self.network.genesis_hash) };
+                msg.write_red();
+            },
+            None => {
+                let msg = Rezzy{ message: format!("{} returned a genesis block without a hash", self.name) };
+                msg.write_red();
+            },
+        }
+    }
+    pub fn head_check(&self) {
+        let block = match eth_result("eth_getBlockByNumber", json!(["latest", false]), self.http_addr.as_str()) {
+            Ok(b) => b,
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get latest block from {}: {}", self.name, e) };
+                msg.write_red();
+                return;
+            },
+        };
+        let ts = match block["timestamp"].as_str().and_then(|t| i64::from_str_radix(t.trim_start_matches("0x"), 16).ok()) {
+            Some(t) => t,
+            None => {
+                let msg = Rezzy{ message: format!("{} returned a latest block without a timestamp", self.name) };
+                msg.write_red();
+                return;
+            },
+        };
+
+        let slot = self.network.seconds_per_slot as i64;
+        let age = Utc::now().timestamp() - ts;
+        if age < -slot {
+            let msg = Rezzy{ message: format!("{} head block is {}s in the future, check the local clock", self.name, -age) };
+            msg.write_yellow();
+        } else if age <= slot * HEAD_WARN_SLOTS as i64 {
+            let msg = Rezzy{ message: format!("{} head block is fresh: {}s old", self.name, age.max(0)) };
+            msg.write_green();
+        } else if age <= slot * HEAD_FAIL_SLOTS as i64 {
+            let msg = Rezzy{ message: format!("{} head block is lagging: {}s old({} slots)", self.name, age, age / slot) };
+            msg.write_yellow();
+        } else {
+            let msg = Rezzy{ message: format!("{} head block is STALE: {}s old({} slots), the node may be stalled", self.name, age, age / slot) };
+            msg.write_red();
+        }
+    }
+    pub fn reference_check(&self) {
+        if self.references.is_empty() {
+            let msg = Rezzy{ message: String::from("No reference execution endpoints configured, skipping head comparison") };
+            msg.write_yellow();
+            return;
+        }
+        let node = Endpoint::new(self.http_addr.as_str());
+        let local = match block_number(&node) {
+            Ok(n) => n,
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get block number from {}: {}", self.name, e) };
+                msg.write_red();
+                return;
+            },
+        };
+
+        let mut heads = Vec::new();
+        let (mut agree, mut disagree) = (0, 0);
+        for reference in &self.references.endpoints {
+            let remote = match block_number(reference) {
+                Ok(n) => n,
+                Err(e) => {
+                    let msg = Rezzy{ message: format!("VALID8R could not reach reference {}: {}", reference.url, e) };
+                    msg.write_yellow();
+                    continue;
+                },
+            };
+            heads.push(remote);
+
+            // compare a few blocks below the common tip so an in-flight reorg isn't reported as a fork
+            let height = local.min(remote).saturating_sub(REORG_DEPTH);
+            match (block_hash(&node, height), block_hash(reference, height)) {
+                (Ok(l), Ok(r)) if l == r => agree += 1,
+                (Ok(l), Ok(r)) => {
+                    let msg = Rezzy{ message: format!("Block {} is {} locally but {} at reference {}", height, l, r, reference.url) };
+                    msg.write_red();
+                    disagree += 1;
+                },
+                (Err(e), _) | (_, Err(e)) => {
+                    let msg = Rezzy{ message: format!("Unable to compare block {} hash with reference {}: {}", height, reference.url, e) };
+                    msg.write_yellow();
+                },
+            }
+        }
+
+        self.references.lag_check(self.name.as_str(), "block", local, &heads);
+        self.references.fork_check(self.name.as_str(), agree, disagree);
+    }
+    pub fn sync_progress(&self, syncing: &serde_json::Value) {
+        let first = match SyncStatus::parse(syncing) {
+            Some(s) => s,
+            None => {
+                let msg = Rezzy{ message: format!("{} is NOT currently synced(could not parse sync progress)", self.name) };
+                msg.write_red();
+                return;
+            },
+        };
+        let mut progress = format!("block {} of {}({:.2}%)", first.current, first.highest, first.percent());
+        if let Some(p) = first.state_percent() {
+            progress = format!("{}, states {} of {}({:.2}%)", progress, first.pulled_states.unwrap(), first.known_states.unwrap(), p);
+        }
+        let msg = Rezzy{ message: format!("{} is NOT currently synced: {}", self.name, progress) };
+        msg.write_red();
+
+        let start = Instant::now();
+        thread::sleep(Duration::from_secs(SYNC_SAMPLE_SECS));
+        let second = match eth_result("eth_syncing", json!([]), self.http_addr.as_str()) {
+            Ok(r) => match SyncStatus::parse(&r) {
+                Some(s) => s,
+                None => {
+                    let msg = Rezzy{ message: format!("{} finished syncing while being sampled", self.name) };
+                    msg.write_green();
+                    return;
+                },
+            },
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to sample sync progress from {}: {}", self.name, e) };
+                msg.write_yellow();
+                return;
+            },
+        };
+
+        let elapsed = start.elapsed().as_secs_f64();
+        match first.eta(&second, elapsed) {
+            Some(eta) => {
+                let rate = second.current.saturating_sub(first.current) as f64 / elapsed;
+                let msg = Rezzy{ message: format!("{} is syncing at {:.1} blocks/s, ETA {}", self.name, rate, fmt_duration(eta)) };
+                msg.write_yellow();
+            },
+            None => {
+                // snap sync downloads state before blocks move, so only call it stalled if states aren't moving either
+                match (first.pulled_states, second.pulled_states) {
+                    (Some(a), Some(b)) if b > a => {
+                        let msg = Rezzy{ message: format!("{} is syncing state at {:.1} states/s, block ETA unknown until state sync completes", self.name, (b - a) as f64 / elapsed) };
+                        msg.write_yellow();
+                    },
+                    _ => {
+                        let msg = Rezzy{ message: format!("{} sync is NOT progressing: no new blocks in {}s", self.name, SYNC_SAMPLE_SECS) };
+                        msg.write_red();
+                    },
+                }
+            },
+        }
     }
     pub fn eth1_check(&self) -> Result<()> {
         let banner = Rezzy{ message: format!("\nETH1 Client Check: {}", self.name) };
@@ -92,7 +331,7 @@ impl Eth1Client {
     
                 match git_req(repo){
                     Ok(r) => {
-                        if ver.contains(&r.as_str()) {
+                        if ver.contains(r.as_str()) {
                             let msg = Rezzy{ message: format!("{}({}) is the latest release: {:?}", self.name, &ver, &r)  };
                             msg.write_green();
                         } else {
@@ -111,48 +350,10 @@ impl Eth1Client {
                 msg.write_red();
             }
         }
-        let res3 = eth_req("net_version", self.http_addr.as_str())?;
-        let r3 = res3.status();
-    
-        match r3 {
-            reqwest::StatusCode::OK => {
-                let j: RpcResponse = res3.json()?;
-                if let Some(re) = j.result { 
-                    if let Some(st) = re.as_str() {
-                        if st.eq("1") && !self.testnet {
-                            let msg = Rezzy{ message: format!("{} is on mainnet", self.name)  };
-                            msg.write_green();
-                        } else if !st.eq("1") && self.testnet {
-                            let msg = Rezzy{ message: format!("{} is on testnet: {}", self.name, st)  };
-                            msg.write_green();
-                        } else {
-                            let msg = Rezzy{ message: format!("{} is currently NOT on mainnet", self.name) };
-                            msg.write_red();
-                        }
-                    }
-                }
-            }
-            _ => {
-                let msg = Rezzy{ message: format!("Unable to get environment from {}", self.name) };
-                msg.write_red();
-            }
-        }
-        match self.infura_req("eth_blockNumber") {
-            Ok(r) => {
-                let inf: RpcResponse = r.json()?;
-                if let Some(infr) = inf.result {
-                    if let Some(infb) = infr.as_str() {
-                        let msg = Rezzy{ message: format!("Valid8r can reach Infura at latest block: {:?}(verify at https://etherscan.io/blocks)", i64::from_str_radix(infb.trim_start_matches("0x"), 16)?) };
-                        msg.write_green();
-                    }
-                }       
-            },
-            Err(e) => {
-                let msg = Rezzy{ message: format!("VALID8R could not reach infura: {:?}", e) };
-                msg.write_red();
-            } 
-        };
-    
+        self.chain_check();
+
+        self.genesis_check();
+
         let res1 = eth_req("eth_blockNumber", self.http_addr.as_str())?;
         let ji: RpcResponse = res1.json()?;
     
@@ -169,19 +370,16 @@ impl Eth1Client {
                             if !re {
                                 if let Some(re) = ji.result {
                                     if let Some(val) = re.as_str() {
-                                        let msg = Rezzy{ message: format!("{} is in sync, latest block: {:?}(verify at https://etherscan.io/blocks)", self.name, i64::from_str_radix(val.trim_start_matches("0x"), 16)?)  };
+                                        let msg = Rezzy{ message: format!("{} reports in sync, latest block: {:?}", self.name, i64::from_str_radix(val.trim_start_matches("0x"), 16)?)  };
                                         msg.write_green();
                                     }
                                 } else {
-                                    let msg = Rezzy{ message: format!("Could not parse sync data") };
+                                    let msg = Rezzy{ message: String::from("Could not parse sync data") };
                                     msg.write_red();
                                 }
                             }
                         } else {
-                            if let Ok(val) = i64::from_str_radix(r["currentBlock"].as_str().unwrap().trim_start_matches("0x"), 16) {
-                                let msg = Rezzy{ message: format!("{} is NOT currently synced: {:?}", self.name, val) };
-                                msg.write_red();
-                            }
+                            self.sync_progress(&r);
                         }
                     },
                     None => {
@@ -195,6 +393,10 @@ impl Eth1Client {
                 msg.write_red();
             }
         }
+        self.head_check();
+
+        self.reference_check();
+
         let res2 = eth_req("net_peerCount", self.http_addr.as_str())?;
         let r2 = res2.status();
     
@@ -232,10 +434,14 @@ impl Eth1Client {
 }
 
 pub fn eth_req(st: &str, url: &str) -> Result<reqwest::blocking::Response> {
+    eth_req_params(st, json!([]), url)
+}
+
+pub fn eth_req_params(st: &str, params: serde_json::Value, url: &str) -> Result<reqwest::blocking::Response> {
     let req = RpcRequest {
         jsonrpc: String::from("2.0"),
         method: String::from(st),
-        params: json!([]),
+        params,
         id: String::from("1"),
     };
 
@@ -258,6 +464,27 @@ pub fn eth_req(st: &str, url: &str) -> Result<reqwest::blocking::Response> {
 
 
 
+pub fn block_number(node: &Endpoint) -> Result<u64> {
+    let res = node.rpc("eth_blockNumber", json!([]))?;
+    match res.as_str() {
+        Some(n) => Ok(u64::from_str_radix(n.trim_start_matches("0x"), 16)?),
+        None => Err(anyhow!("eth_blockNumber returned {}", res)),
+    }
+}
+
+pub fn block_hash(node: &Endpoint, number: u64) -> Result<String> {
+    let block = node.rpc("eth_getBlockByNumber", json!([format!("0x{:x}", number), false]))?;
+    match block["hash"].as_str() {
+        Some(h) => Ok(h.to_lowercase()),
+        None => Err(anyhow!("block {} has no hash", number)),
+    }
+}
+
+// result field of a successful json rpc call, errors on http or rpc failure
+pub fn eth_result(st: &str, params: serde_json::Value, url: &str) -> Result<serde_json::Value> {
+    Endpoint::new(url).rpc(st, params)
+}
+
 fn git_req(repo: &str) -> Result<String> {
     let client = reqwest::blocking::Client::new();
     let res = client.get(repo)
@@ -277,12 +504,32 @@ fn git_req(repo: &str) -> Result<String> {
     match j["tag_name"].as_str() {
         Some(v) => x = v,
         None => {
-            let msg = Rezzy{ message: format!("Could not pull client release version") };
+            let msg = Rezzy{ message: String::from("Could not pull client release version") };
             msg.write_red();
         },
     }
     Ok(String::from(x))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn sync_status_progress() {
+        let first = SyncStatus::parse(&json!({
+            "startingBlock": "0x0",
+            "currentBlock": "0x64",
+            "highestBlock": "0x3e8",
+            "pulledStates": "0x10",
+            "knownStates": "0x40",
+        })).unwrap();
+        assert_eq!(first.percent(), 10.0);
+        assert_eq!(first.state_percent(), Some(25.0));
 
+        let second = SyncStatus{ current: 150, ..first };
+        assert_eq!(first.eta(&second, 5.0), Some(85));
+        assert_eq!(first.eta(&first, 5.0), None);
+        assert_eq!(SyncStatus::parse(&json!(false)), None);
+    }
+}
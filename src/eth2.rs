@@ -1,15 +1,23 @@
 extern crate reqwest;
 use serde::{Serialize, Deserialize};
 use serde_json::json;
-use anyhow::Result;
-use crate::output::Rezzy;
-use crate::eth1::{eth_req, RpcResponse};
+use anyhow::{anyhow, Result};
+use std::thread;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use crate::output::{Rezzy, fmt_duration};
+use crate::eth1::{eth_req, block_hash, RpcResponse, SYNC_SAMPLE_SECS};
+use crate::network::{Network, chain_name};
+use crate::reference::{Endpoint, References};
 
 static LIGHTHOUSE_GIT: &str = "https://api.github.com/repos/sigp/lighthouse/releases/latest";
 static PRYSM_GIT: &str = "https://api.github.com/repos/prysmaticlabs/prysm/releases/latest";
 static NIMBUS_GIT: &str = "https://api.github.com/repos/status-im/nimbus-eth2/releases/latest";
 static TEKU_GIT: &str = "https://api.github.com/repos/ConsenSys/teku/releases/latest";
 
+// slots below the common head at which local and reference block roots are compared
+static REORG_DEPTH: u64 = 2;
+
 #[derive(Serialize, Deserialize, Debug)]
 struct Eth2Response {
     data: Option<serde_json::Value>,
@@ -43,38 +51,100 @@ fn git_req(repo: &str) -> Result<String> {
     match j["tag_name"].as_str() {
         Some(v) => x = v,
         None => {
-            let msg = Rezzy{ message: format!("Could not pull client release version") };
+            let msg = Rezzy{ message: String::from("Could not pull client release version") };
             msg.write_red();
         },
     }
     Ok(String::from(x))
 }
 
-fn eth2_sync_check(endpoint: &str) -> Result<bool> {
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct BeaconSync {
+    head_slot: u64,
+    sync_distance: u64,
+    is_syncing: bool,
+    is_optimistic: bool,
+    el_offline: bool,
+}
+
+impl BeaconSync {
+    fn parse(j: &serde_json::Value) -> Option<BeaconSync> {
+        Some(BeaconSync{
+            head_slot: j["head_slot"].as_str()?.parse().ok()?,
+            sync_distance: j["sync_distance"].as_str()?.parse().ok()?,
+            is_syncing: j["is_syncing"].as_bool()?,
+            // older beacon apis don't report these
+            is_optimistic: j["is_optimistic"].as_bool().unwrap_or(false),
+            el_offline: j["el_offline"].as_bool().unwrap_or(false),
+        })
+    }
+    // seconds to close the sync distance, the head keeps moving one slot per `seconds_per_slot` while we catch up
+    fn eta(&self, later: &BeaconSync, elapsed: f64, seconds_per_slot: u64) -> Option<u64> {
+        let rate = later.head_slot.saturating_sub(self.head_slot) as f64 / elapsed;
+        let closing = rate - 1.0 / seconds_per_slot as f64;
+        if elapsed <= 0.0 || closing <= 0.0 {
+            return None;
+        }
+        Some((later.sync_distance as f64 / closing) as u64)
+    }
+}
+
+fn beacon_sync(endpoint: &str) -> Result<Option<BeaconSync>> {
     let res = eth2_req(endpoint)?;
     let pay: Eth2Response = res.json()?;
-    let mut x = true;
+    Ok(pay.data.as_ref().and_then(BeaconSync::parse))
+}
 
-    if let Some(j) = pay.data{
-        match j["is_syncing"].as_bool() {
-            Some(v) => {
-                if v {
-                    if let Some(re) = j["sync_distance"].as_str() {
-                        let val: usize = re.parse()?;
-                        let msg = Rezzy{ message: format!("Sync Distance: {:?}", val) };
-                        msg.write_red();
-                    }
-                }
-                x = v;
-            },
-            None => {
-                let msg = Rezzy{ message: format!("Could not get syncing status of ETH2 validator") };
-                msg.write_red();
-            },
-        }
+fn eth2_sync_check(eth2: &str, endpoint: &str, network: &Network) -> Result<()> {
+    let first = match beacon_sync(endpoint)? {
+        Some(s) => s,
+        None => {
+            let msg = Rezzy{ message: String::from("Could not get syncing status of ETH2 validator") };
+            msg.write_red();
+            return Ok(());
+        },
     };
 
-    Ok(x)
+    if !first.is_syncing {
+        println!("{} head slot: {}", eth2, first.head_slot);
+    }
+    // an optimistic node follows the chain without verified execution payloads, it must not attest
+    if first.is_optimistic {
+        let msg = Rezzy{ message: format!("{} is OPTIMISTIC at head slot {}: execution payloads are unverified, it can't safely attest", eth2, first.head_slot) };
+        msg.write_red();
+    }
+    if !first.is_syncing {
+        return Ok(());
+    }
+
+    let msg = Rezzy{ message: format!("{} head slot {}, Sync Distance: {:?}", eth2, first.head_slot, first.sync_distance) };
+    msg.write_red();
+
+    let start = Instant::now();
+    thread::sleep(Duration::from_secs(SYNC_SAMPLE_SECS));
+    let second = match beacon_sync(endpoint) {
+        Ok(Some(s)) => s,
+        _ => {
+            let msg = Rezzy{ message: format!("Unable to sample sync progress from {}", eth2) };
+            msg.write_yellow();
+            return Ok(());
+        },
+    };
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let rate = second.head_slot.saturating_sub(first.head_slot) as f64 / elapsed;
+    match first.eta(&second, elapsed, network.seconds_per_slot) {
+        Some(eta) => {
+            let msg = Rezzy{ message: format!("{} is syncing at {:.1} slots/s, ETA {}", eth2, rate, fmt_duration(eta)) };
+            msg.write_yellow();
+        },
+        None => {
+            let msg = Rezzy{ message: format!("{} sync is NOT catching up: {:.2} slots/s over {}s", eth2, rate, SYNC_SAMPLE_SECS) };
+            msg.write_red();
+        },
+    }
+
+    Ok(())
 }
 
 fn eth2_peer_count(endpoint: &str) -> Result<usize> {
@@ -88,7 +158,7 @@ fn eth2_peer_count(endpoint: &str) -> Result<usize> {
                 x = val
             },
             None => {
-                let msg = Rezzy{ message: format!("Could not get peer count of ETH2 validator") };
+                let msg = Rezzy{ message: String::from("Could not get peer count of ETH2 validator") };
                 msg.write_red();
             },
         }
@@ -102,7 +172,7 @@ fn parse_ver(pay: &Eth2Response) -> Result<String> {
         match j["version"].as_str() {
             Some(v) => x = v,
             None => {
-                let msg = Rezzy{ message: format!("Could not pull client release version") };
+                let msg = Rezzy{ message: String::from("Could not pull client release version") };
                 msg.write_red();
             },
         }
@@ -111,13 +181,528 @@ fn parse_ver(pay: &Eth2Response) -> Result<String> {
     Ok(String::from(x))
 }
 
-pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
+fn eth2_network_check(eth2: &str, endpoint: &str, network: &Network) -> Result<()> {
+    let res = eth2_req(endpoint)?;
+    let pay: Eth2Response = res.json()?;
+
+    let data = match pay.data {
+        Some(d) => d,
+        None => {
+            let msg = Rezzy{ message: format!("Could not get deposit contract of {}", eth2) };
+            msg.write_red();
+            return Ok(());
+        },
+    };
+    let chain_id: u64 = data["chain_id"].as_str().unwrap_or_default().parse()?;
+    let address = data["address"].as_str().unwrap_or_default().to_lowercase();
+
+    if chain_id != network.chain_id {
+        let msg = Rezzy{ message: format!("{} is on {}(deposit chain id {}) but {}(chain id {}) was expected", eth2, chain_name(chain_id), chain_id, network.name, network.chain_id) };
+        msg.write_red();
+    } else if address != network.deposit_contract.to_lowercase() {
+        let msg = Rezzy{ message: format!("{} deposit contract {} does NOT match {} deposit contract {}", eth2, address, network.name, network.deposit_contract) };
+        msg.write_red();
+    } else {
+        let msg = Rezzy{ message: format!("{} is on {}(deposit chain id {})", eth2, network.name, chain_id) };
+        msg.write_green();
+    }
+    Ok(())
+}
+
+// true when the node is on the network's genesis
+fn eth2_genesis_check(eth2: &str, node: &Endpoint, network: &Network) -> bool {
+    let j = match node.get("/eth/v1/beacon/genesis") {
+        Ok(j) => j,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get genesis from {}: {}", eth2, e) };
+            msg.write_red();
+            return false;
+        },
+    };
+    let data = &j["data"];
+    let time: u64 = data["genesis_time"].as_str().and_then(|t| t.parse().ok()).unwrap_or_default();
+    let gvr = data["genesis_validators_root"].as_str().unwrap_or_default().to_lowercase();
+    let version = data["genesis_fork_version"].as_str().unwrap_or_default().to_lowercase();
+
+    let mut ok = true;
+    if gvr != network.genesis_validators_root.to_lowercase() {
+        let msg = Rezzy{ message: format!("{} genesis validators root {} does NOT match {}({}), check its --network flag", eth2, gvr, network.name, network.genesis_validators_root) };
+        msg.write_red();
+        ok = false;
+    }
+    if time != network.genesis_time {
+        let msg = Rezzy{ message: format!("{} genesis time {} does NOT match {}({})", eth2, time, network.name, network.genesis_time) };
+        msg.write_red();
+        ok = false;
+    }
+    if version != network.genesis_fork_version.to_lowercase() {
+        let msg = Rezzy{ message: format!("{} genesis fork version {} does NOT match {}({})", eth2, version, network.name, network.genesis_fork_version) };
+        msg.write_red();
+        ok = false;
+    }
+    if ok {
+        let msg = Rezzy{ message: format!("{} genesis time, validators root and fork version match {}", eth2, network.name) };
+        msg.write_green();
+    }
+    ok
+}
+
+// epoch clients schedule a fork they know about but that has no date yet
+static FAR_FUTURE_EPOCH: u64 = u64::MAX;
+
+#[derive(Debug, PartialEq, Clone)]
+struct ScheduledFork {
+    version: String,
+    epoch: u64,
+}
+
+impl ScheduledFork {
+    fn parse(j: &serde_json::Value) -> Option<ScheduledFork> {
+        Some(ScheduledFork{
+            version: j["current_version"].as_str()?.to_lowercase(),
+            epoch: j["epoch"].as_str()?.parse().ok()?,
+        })
+    }
+}
+
+// every fork of the network must be scheduled at its epoch, anything else the client schedules must not have a date
+fn fork_diff(network: &Network, schedule: &[ScheduledFork]) -> Vec<String> {
+    let mut diffs = Vec::new();
+    for fork in &network.forks {
+        match schedule.iter().find(|s| s.version == fork.version.to_lowercase()) {
+            Some(s) if s.epoch == fork.epoch => (),
+            Some(s) => diffs.push(format!("{}({}) is scheduled at epoch {} but {} activates it at {}", fork.name, fork.version, s.epoch, network.name, fork.epoch)),
+            None => diffs.push(format!("{}({}) at epoch {} is NOT scheduled, the client may need an update", fork.name, fork.version, fork.epoch)),
+        }
+    }
+    for s in schedule {
+        let known = s.version == network.genesis_fork_version.to_lowercase()
+            || network.forks.iter().any(|f| f.version.to_lowercase() == s.version);
+        if !known && s.epoch != FAR_FUTURE_EPOCH {
+            diffs.push(format!("unknown fork version {} is scheduled at epoch {}", s.version, s.epoch));
+        }
+    }
+    diffs
+}
+
+fn eth2_fork_schedule_check(eth2: &str, node: &Endpoint, network: &Network) {
+    let j = match node.get("/eth/v1/config/fork_schedule") {
+        Ok(j) => j,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get fork schedule from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+    let schedule: Vec<ScheduledFork> = j["data"].as_array()
+        .map(|a| a.iter().filter_map(ScheduledFork::parse).collect())
+        .unwrap_or_default();
+
+    let diffs = fork_diff(network, &schedule);
+    if diffs.is_empty() {
+        let msg = Rezzy{ message: format!("{} fork schedule matches {}({} forks)", eth2, network.name, network.forks.len()) };
+        msg.write_green();
+    }
+    for d in diffs {
+        let msg = Rezzy{ message: format!("{} fork schedule: {}", eth2, d) };
+        msg.write_red();
+    }
+}
+
+// (key, expected, reported) for every spec value that differs from the network
+fn spec_diff(expected: &BTreeMap<String, String>, spec: &serde_json::Value) -> Vec<(String, String, Option<String>)> {
+    expected.iter()
+        .filter_map(|(k, v)| {
+            let reported = spec[k.as_str()].as_str().map(|r| r.to_lowercase());
+            match reported {
+                Some(r) if &r == v => None,
+                r => Some((k.clone(), v.clone(), r)),
+            }
+        })
+        .collect()
+}
+
+fn eth2_spec_check(eth2: &str, node: &Endpoint, network: &Network) {
+    let j = match node.get("/eth/v1/config/spec") {
+        Ok(j) => j,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get spec config from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+    let expected = network.spec();
+    let diffs = spec_diff(&expected, &j["data"]);
+    if diffs.is_empty() {
+        let msg = Rezzy{ message: format!("{} spec config matches {}({} values checked)", eth2, network.name, expected.len()) };
+        msg.write_green();
+    }
+    for (key, want, got) in diffs {
+        match got {
+            Some(g) => {
+                let msg = Rezzy{ message: format!("{} spec {} is {} but {} expects {}, check for a leftover config file", eth2, key, g, network.name, want) };
+                msg.write_red();
+            },
+            None => {
+                let msg = Rezzy{ message: format!("{} spec does not report {}({} expected), the client may need an update", eth2, key, want) };
+                msg.write_yellow();
+            },
+        }
+    }
+}
+
+// slot and root of a beacon block header, `block_id` is head or a slot number
+pub fn beacon_header(node: &Endpoint, block_id: &str) -> Result<(u64, String)> {
+    let j = node.get(format!("/eth/v1/beacon/headers/{}", block_id).as_str())?;
+    let slot: u64 = j["data"]["header"]["message"]["slot"].as_str().unwrap_or_default().parse()?;
+    let root = j["data"]["root"].as_str().unwrap_or_default().to_lowercase();
+    Ok((slot, root))
+}
+
+fn eth2_reference_check(eth2: &str, base_path: &str, refs: &References) {
+    if refs.is_empty() {
+        let msg = Rezzy{ message: String::from("No reference beacon endpoints configured, skipping head comparison") };
+        msg.write_yellow();
+        return;
+    }
+    let node = Endpoint::new(base_path);
+    let local = match beacon_header(&node, "head") {
+        Ok((slot, _)) => slot,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get head slot from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+
+    let mut heads = Vec::new();
+    let (mut agree, mut disagree) = (0, 0);
+    for reference in &refs.endpoints {
+        let remote = match beacon_header(reference, "head") {
+            Ok((slot, _)) => slot,
+            Err(e) => {
+                let msg = Rezzy{ message: format!("VALID8R could not reach reference {}: {}", reference.url, e) };
+                msg.write_yellow();
+                continue;
+            },
+        };
+        heads.push(remote);
+
+        // empty slots have no header, so this can come back inconclusive
+        let slot = local.min(remote).saturating_sub(REORG_DEPTH).to_string();
+        match (beacon_header(&node, slot.as_str()), beacon_header(reference, slot.as_str())) {
+            (Ok((_, l)), Ok((_, r))) if l == r => agree += 1,
+            (Ok((_, l)), Ok((_, r))) => {
+                let msg = Rezzy{ message: format!("Slot {} is {} locally but {} at reference {}", slot, l, r, reference.url) };
+                msg.write_red();
+                disagree += 1;
+            },
+            (Err(e), _) | (_, Err(e)) => {
+                let msg = Rezzy{ message: format!("Unable to compare slot {} root with reference {}: {}", slot, reference.url, e) };
+                msg.write_yellow();
+            },
+        }
+    }
+
+    refs.lag_check(eth2, "slot", local, &heads);
+    refs.fork_check(eth2, agree, disagree);
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Health {
+    Ready,
+    Syncing,
+    Unavailable,
+    Unknown(u16),
+}
+
+impl Health {
+    fn from_status(code: u16) -> Health {
+        match code {
+            200 => Health::Ready,
+            206 => Health::Syncing,
+            503 => Health::Unavailable,
+            c => Health::Unknown(c),
+        }
+    }
+}
+
+pub fn node_health(node: &Endpoint) -> Result<Health> {
+    Ok(Health::from_status(node.status("/eth/v1/node/health")?.as_u16()))
+}
+
+// the standard liveness signal, in place of guessing from the version and syncing calls
+fn eth2_health_check(eth2: &str, node: &Endpoint) {
+    match node_health(node) {
+        Ok(Health::Ready) => {
+            let msg = Rezzy{ message: format!("{} is healthy(200): synced and ready to perform duties", eth2) };
+            msg.write_green();
+        },
+        Ok(Health::Syncing) => {
+            let msg = Rezzy{ message: format!("{} is up but syncing(206): it can't perform duties yet", eth2) };
+            msg.write_yellow();
+        },
+        Ok(Health::Unavailable) => {
+            let msg = Rezzy{ message: format!("{} is NOT healthy(503): not initialized or having issues", eth2) };
+            msg.write_red();
+        },
+        Ok(Health::Unknown(c)) => {
+            let msg = Rezzy{ message: format!("{} returned unexpected health status {}", eth2, c) };
+            msg.write_yellow();
+        },
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get health from {}: {}", eth2, e) };
+            msg.write_red();
+        },
+    }
+}
+
+// client specific endpoints reporting on the execution client connection, beyond the standard api
+fn el_status_path(eth2: &str) -> Option<&'static str> {
+    match eth2 {
+        "PRYSM" => Some("/eth/v1alpha1/node/eth1/connections"),
+        "TEKU" => Some("/teku/v1/admin/readiness"),
+        _ => None,
+    }
+}
+
+fn el_status_check(eth2: &str, node: &Endpoint) {
+    let path = match el_status_path(eth2) {
+        Some(p) => p,
+        None => return,
+    };
+    match eth2 {
+        "PRYSM" => match node.get(path) {
+            Ok(j) => {
+                let err = j["currentConnectionError"].as_str().unwrap_or_default();
+                let addr = j["currentAddress"].as_str().unwrap_or_default();
+                if err.is_empty() {
+                    let msg = Rezzy{ message: format!("{} is connected to its execution client at {}", eth2, addr) };
+                    msg.write_green();
+                } else {
+                    let msg = Rezzy{ message: format!("{} execution client connection to {} FAILING: {}", eth2, addr, err) };
+                    msg.write_red();
+                }
+            },
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get execution client connection from {}: {}", eth2, e) };
+                msg.write_yellow();
+            },
+        },
+        _ => match node.status(path) {
+            Ok(s) if s.is_success() => {
+                let msg = Rezzy{ message: format!("{} reports ready, including its execution client", eth2) };
+                msg.write_green();
+            },
+            Ok(s) => {
+                let msg = Rezzy{ message: format!("{} reports NOT ready({}), check its execution client connection", eth2, s) };
+                msg.write_red();
+            },
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get readiness from {}: {}", eth2, e) };
+                msg.write_yellow();
+            },
+        },
+    }
+}
+
+// confirms the beacon node is driven by the configured execution client, not just that both are up
+pub fn execution_link_check(eth2: &str, client_addr: &str, eth1: &str, eth1_addr: &str) {
+    // nimbus is queried over its json rpc api, which exposes none of this
+    if eth2 == "NIMBUS" {
+        println!("{} execution client connection can't be checked over json rpc, skipping", eth2);
+        return;
+    }
+    let node = Endpoint::new(client_addr);
+
+    match beacon_sync(format!("{}/eth/v1/node/syncing", client_addr).as_str()) {
+        Ok(Some(s)) if s.el_offline => {
+            let msg = Rezzy{ message: format!("{} reports its execution client is OFFLINE(el_offline)", eth2) };
+            msg.write_red();
+        },
+        Ok(Some(_)) => {
+            let msg = Rezzy{ message: format!("{} reports its execution client is online", eth2) };
+            msg.write_green();
+        },
+        Ok(None) | Err(_) => {
+            let msg = Rezzy{ message: format!("Unable to get execution client status from {}", eth2) };
+            msg.write_yellow();
+        },
+    }
+
+    // 503 while the el is offline means the beacon node can't make progress without it
+    if let Ok(Health::Unavailable) = node_health(&node) {
+        let msg = Rezzy{ message: format!("{} health is unavailable(503), it can't follow the chain without a working execution client", eth2) };
+        msg.write_red();
+    }
+
+    el_status_check(eth2, &node);
+
+    // the execution payload of the beacon head must be a block the local execution client knows
+    let block = match node.get("/eth/v2/beacon/blocks/head") {
+        Ok(b) => b,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get head block from {}: {}", eth2, e) };
+            msg.write_yellow();
+            return;
+        },
+    };
+    let payload = &block["data"]["message"]["body"]["execution_payload"];
+    let number: u64 = match payload["block_number"].as_str().and_then(|n| n.parse().ok()) {
+        Some(n) => n,
+        None => {
+            println!("{} head block has no execution payload, skipping payload comparison", eth2);
+            return;
+        },
+    };
+    let expected = payload["block_hash"].as_str().unwrap_or_default().to_lowercase();
+    match block_hash(&Endpoint::new(format!("http://{}", eth1_addr).as_str()), number) {
+        Ok(h) if h == expected => {
+            let msg = Rezzy{ message: format!("{} head execution payload(block {}) matches {}", eth2, number, eth1) };
+            msg.write_green();
+        },
+        Ok(h) => {
+            let msg = Rezzy{ message: format!("{} head execution payload block {} is {} but {} has {}: they follow DIFFERENT chains", eth2, number, expected, eth1, h) };
+            msg.write_red();
+        },
+        Err(e) => {
+            let msg = Rezzy{ message: format!("{} does not have block {} from the {} head payload({}), it may be syncing or {} uses another execution client", eth1, number, eth2, e, eth2) };
+            msg.write_red();
+        },
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Checkpoint {
+    epoch: u64,
+    root: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Finality {
+    finalized: Checkpoint,
+    justified: Checkpoint,
+}
+
+impl Finality {
+    fn parse(j: &serde_json::Value) -> Option<Finality> {
+        let checkpoint = |c: &serde_json::Value| -> Option<Checkpoint> {
+            Some(Checkpoint{
+                epoch: c["epoch"].as_str()?.parse().ok()?,
+                root: c["root"].as_str()?.to_lowercase(),
+            })
+        };
+        Some(Finality{
+            finalized: checkpoint(&j["finalized"])?,
+            justified: checkpoint(&j["current_justified"])?,
+        })
+    }
+    // epochs between the head and the finalized checkpoint, 2 on a healthy chain
+    fn lag(&self, head_slot: u64, slots_per_epoch: u64) -> u64 {
+        (head_slot / slots_per_epoch.max(1)).saturating_sub(self.finalized.epoch)
+    }
+}
+
+fn finality(node: &Endpoint) -> Result<Finality> {
+    let j = node.get("/eth/v1/beacon/states/head/finality_checkpoints")?;
+    Finality::parse(&j["data"]).ok_or_else(|| anyhow!("malformed finality checkpoints"))
+}
+
+// checkpoints at the same epoch must have the same root, a later epoch elsewhere is just timing
+fn checkpoint_cmp(name: &str, url: &str, local: &Checkpoint, remote: &Checkpoint) -> Option<bool> {
+    if local.epoch != remote.epoch {
+        println!("{} checkpoint is epoch {} locally but {} at reference {}, not comparable", name, local.epoch, remote.epoch, url);
+        return None;
+    }
+    if local.root != remote.root {
+        let msg = Rezzy{ message: format!("{} checkpoint epoch {} is {} locally but {} at reference {}", name, local.epoch, local.root, remote.root, url) };
+        msg.write_red();
+    }
+    Some(local.root == remote.root)
+}
+
+fn eth2_finality_check(eth2: &str, base_path: &str, network: &Network, refs: &References, max_lag: u64) {
+    let node = Endpoint::new(base_path);
+    let (local, head) = match (finality(&node), beacon_header(&node, "head")) {
+        (Ok(f), Ok((slot, _))) => (f, slot),
+        (Err(e), _) | (_, Err(e)) => {
+            let msg = Rezzy{ message: format!("Unable to get finality checkpoints from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+
+    let mut remotes = Vec::new();
+    for reference in &refs.endpoints {
+        match finality(reference) {
+            Ok(f) => remotes.push((reference.url.as_str(), f)),
+            Err(e) => {
+                let msg = Rezzy{ message: format!("VALID8R could not get finality from reference {}: {}", reference.url, e) };
+                msg.write_yellow();
+            },
+        }
+    }
+
+    let lag = local.lag(head, network.slots_per_epoch);
+    let ahead = remotes.iter().map(|(_, f)| f.finalized.epoch).max().unwrap_or(0);
+    if lag <= max_lag {
+        let msg = Rezzy{ message: format!("{} finalized epoch {} is {} epochs behind the head(limit {})", eth2, local.finalized.epoch, lag, max_lag) };
+        msg.write_green();
+    } else if ahead > local.finalized.epoch {
+        let msg = Rezzy{ message: format!("{} finalized epoch {} is {} epochs BEHIND the head while references have finalized epoch {}", eth2, local.finalized.epoch, lag, ahead) };
+        msg.write_red();
+    } else if !remotes.is_empty() {
+        let msg = Rezzy{ message: format!("{} finalized epoch {} is {} epochs behind the head, the references agree: the NETWORK is not finalizing", eth2, local.finalized.epoch, lag) };
+        msg.write_yellow();
+    } else {
+        let msg = Rezzy{ message: format!("{} finalized epoch {} is {} epochs BEHIND the head(limit {})", eth2, local.finalized.epoch, lag, max_lag) };
+        msg.write_red();
+    }
+
+    if refs.is_empty() {
+        return;
+    }
+    let (mut fin_agree, mut fin_disagree, mut just_agree, mut just_disagree) = (0, 0, 0, 0);
+    for (url, remote) in &remotes {
+        match checkpoint_cmp("Finalized", url, &local.finalized, &remote.finalized) {
+            Some(true) => fin_agree += 1,
+            Some(false) => fin_disagree += 1,
+            None => (),
+        }
+        match checkpoint_cmp("Justified", url, &local.justified, &remote.justified) {
+            Some(true) => just_agree += 1,
+            Some(false) => just_disagree += 1,
+            None => (),
+        }
+    }
+    refs.fork_check(format!("{} finalized checkpoint", eth2).as_str(), fin_agree, fin_disagree);
+    refs.fork_check(format!("{} justified checkpoint", eth2).as_str(), just_agree, just_disagree);
+}
+
+pub fn eth2_check(eth2: &str, client_addr: String, network: &Network, refs: &References, finality_lag: u64) -> Result<()> {
     let banner = Rezzy{ message: format!("\nETH2 Client Check: {}", eth2) };
     banner.bold();
 
 
     let base_path = client_addr.as_str();
 
+    // nimbus is queried over its json rpc api, everything else serves the standard beacon api
+    if eth2 != "NIMBUS" {
+        if let Err(e) = eth2_network_check(eth2, format!("{}/eth/v1/config/deposit_contract", base_path).as_str(), network) {
+            let msg = Rezzy{ message: format!("Unable to get network from {}: {}", eth2, e) };
+            msg.write_red();
+        }
+        let node = Endpoint::new(base_path);
+        // a different genesis makes every fork version differ, that's one problem not many
+        if eth2_genesis_check(eth2, &node, network) {
+            eth2_fork_schedule_check(eth2, &node, network);
+            eth2_spec_check(eth2, &node, network);
+        }
+        eth2_health_check(eth2, &node);
+        eth2_reference_check(eth2, base_path, refs);
+        eth2_finality_check(eth2, base_path, network, refs, finality_lag);
+    }
+
     match eth2 {
         "NIMBUS" => {
             let res4 = eth_req("getNodeVersion", client_addr.as_str())?;
@@ -136,7 +721,7 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
 
                     match git_req(repo){
                         Ok(r) => {
-                            if ver.contains(&r.as_str()) {
+                            if ver.contains(r.as_str()) {
                                 let msg = Rezzy{ message: format!("{}({}) is the latest release: {:?}", eth2, &ver, &r)  };
                                 msg.write_green();
                             } else {
@@ -166,7 +751,7 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
                         Some(r) => {
                             if let Some(re) = r.as_bool() {
                                 if !re {
-                                    let msg = Rezzy{ message: format!("{} is in sync (verify at https://etherscan.io/blocks)", eth2)};
+                                    let msg = Rezzy{ message: format!("{} is in sync", eth2)};
                                     msg.write_green();
                                 }
                             } else {
@@ -219,34 +804,6 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
             }
         }
         "PRYSM" => {
-            fn eth2_prysm_sync_check(prysm_addr: &str) -> Result<bool> {
-
-                let client = reqwest::blocking::Client::new();
-                let res = client.get(prysm_addr)
-                    .header("User-Agent", "request")
-                    .send()?
-                    .text()?;
-            
-                let j: serde_json::Value = match serde_json::from_str(res.as_str()) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        let msg = Rezzy{ message: format!("Error reading request: {:?}", e) };
-                        msg.write_red();
-                        json![""]
-                    },
-                };
-            
-                let mut x = true;
-                match j["syncing"].as_bool() {
-                    Some(v) => x = v,
-                    None => {
-                        let msg = Rezzy{ message: format!("Could not get syncing status of ETH2 validator") };
-                        msg.write_red();
-                    },
-                }
-                Ok(x)
-            }
-
             fn eth2_prysm_peer_count(endpoint: &str) -> Result<usize> {
                 let res = eth2_req(endpoint)?;
                 let mut x = 0;
@@ -263,7 +820,7 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
                 match Option::Some(k["peers"].as_array().unwrap().len()) {
                     Some(v) => x = v,
                     None => {
-                        let msg = Rezzy{ message: format!("Could not get peer count of ETH2 validator") };
+                        let msg = Rezzy{ message: String::from("Could not get peer count of ETH2 validator") };
                         msg.write_red();
                     }
                 }
@@ -282,7 +839,7 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
         
                     match git_req(repo){
                         Ok(r) => {
-                            if ver.contains(&r.as_str()) {
+                            if ver.contains(r.as_str()) {
                                 let msg = Rezzy{ message: format!("{}({}) is the latest release: {:?}", eth2, &ver, &r)  };
                                 msg.write_green();
                             } else {
@@ -302,21 +859,9 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
                 }
             }
 
-            match eth2_prysm_sync_check(format!("{}/eth/v1alpha1/node/syncing", base_path).as_str()) {
-                Ok(r) => {
-                    if !r {
-                        let msg = Rezzy{ message: format!("{} is currently synced!", eth2) };
-                        msg.write_green();
-                    }
-                    else {
-                        let msg = Rezzy{ message: format!("{} is NOT currently synced", eth2) };
-                        msg.write_red();
-                    }
-                },
-                Err(e) => {
-                    println!("{}", e)
-                }
-            };
+            if let Err(e) = eth2_sync_check(eth2, format!("{}/eth/v1/node/syncing", base_path).as_str(), network) {
+                println!("{}", e)
+            }
 
             match eth2_prysm_peer_count(format!("{}/eth/v1alpha1/node/peers", base_path).as_str()){
                 Ok(r) => {
@@ -351,7 +896,7 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
         
                     match git_req(repo){
                         Ok(r) => {
-                            if ver.contains(&r.as_str()) {
+                            if ver.contains(r.as_str()) {
                                 let msg = Rezzy{ message: format!("{}({}) is the latest release: {:?}", eth2, &ver, &r)  };
                                 msg.write_green();
                             } else {
@@ -371,22 +916,9 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
                 }
             }
 
-            match eth2_sync_check(format!("{}/eth/v1/node/syncing", base_path).as_str()) {
-                Ok(r) => {
-                    
-                    if !r {
-                        let msg = Rezzy{ message: format!("{} is currently synced!", eth2) };
-                        msg.write_green();
-                    }
-                    else {
-                        let msg = Rezzy{ message: format!("{} is NOT currently synced", eth2) };
-                        msg.write_red();
-                    }
-                },
-                Err(e) => {
-                    println!("{}", e)
-                }
-            };
+            if let Err(e) = eth2_sync_check(eth2, format!("{}/eth/v1/node/syncing", base_path).as_str(), network) {
+                println!("{}", e)
+            }
 
             match eth2_peer_count(format!("{}/eth/v1/node/peer_count", base_path).as_str()){
                 Ok(r) => {
@@ -405,4 +937,80 @@ pub fn eth2_check(eth2: &str, client_addr: String) -> Result<()> {
         }
     };
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beacon_sync_eta() {
+        let first = BeaconSync::parse(&json!({
+            "head_slot": "1000",
+            "sync_distance": "6000",
+            "is_syncing": true,
+            "is_optimistic": true,
+        })).unwrap();
+        assert!(first.is_optimistic && !first.el_offline);
+
+        // 10 slots/s against a chain moving 1 slot/12s
+        let second = BeaconSync{ head_slot: 1050, sync_distance: 5950, ..first };
+        assert_eq!(first.eta(&second, 5.0, 12), Some(600));
+        assert_eq!(first.eta(&first, 5.0, 12), None);
+    }
+
+    #[test]
+    fn health_status_codes() {
+        assert_eq!(Health::from_status(200), Health::Ready);
+        assert_eq!(Health::from_status(206), Health::Syncing);
+        assert_eq!(Health::from_status(503), Health::Unavailable);
+        assert_eq!(Health::from_status(404), Health::Unknown(404));
+    }
+
+    #[test]
+    fn finality_checkpoint_lag() {
+        let fin = Finality::parse(&json!({
+            "previous_justified": { "epoch": "98", "root": "0xAA" },
+            "current_justified": { "epoch": "99", "root": "0xbb" },
+            "finalized": { "epoch": "98", "root": "0xAA" },
+        })).unwrap();
+        assert_eq!(fin.finalized.root, "0xaa");
+        assert_eq!(fin.justified.epoch, 99);
+        // head slot 3200 is epoch 100
+        assert_eq!(fin.lag(3200, 32), 2);
+        assert_eq!(fin.lag(3000, 32), 0);
+    }
+
+    #[test]
+    fn fork_schedule_diff() {
+        let net = Network::hoodi();
+        let mut schedule: Vec<ScheduledFork> = net.forks.iter()
+            .map(|f| ScheduledFork{ version: f.version.clone(), epoch: f.epoch })
+            .collect();
+        schedule.push(ScheduledFork{ version: String::from("0x80000910"), epoch: FAR_FUTURE_EPOCH });
+        assert!(fork_diff(&net, &schedule).is_empty());
+
+        schedule.pop();
+        schedule.pop();
+        schedule[4].epoch = 1;
+        assert_eq!(fork_diff(&net, &schedule).len(), 2);
+    }
+
+    #[test]
+    fn spec_config_diff() {
+        let net = Network::mainnet();
+        let mut spec: serde_json::Value = net.spec().into_iter()
+            .map(|(k, v)| (k, json!(v.to_uppercase())))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+        spec["CONFIG_NAME"] = json!("mainnet");
+        assert!(spec_diff(&net.spec(), &spec).is_empty());
+
+        spec["SECONDS_PER_SLOT"] = json!("6");
+        spec.as_object_mut().unwrap().remove("FULU_FORK_EPOCH");
+        let diffs = spec_diff(&net.spec(), &spec);
+        assert_eq!(diffs.len(), 2);
+        assert!(diffs.contains(&(String::from("SECONDS_PER_SLOT"), String::from("12"), Some(String::from("6")))));
+        assert!(diffs.contains(&(String::from("FULU_FORK_EPOCH"), String::from("411392"), None)));
+    }
+}
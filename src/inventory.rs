@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet};
+use serde::Deserialize;
+use anyhow::Result;
+use crate::output::Rezzy;
+use crate::reference::Endpoint;
+use crate::keystore;
+use crate::validator::short_key;
+use crate::vc;
+
+// every validator client that may hold keys, for catching the same key loaded twice
+#[derive(Debug, PartialEq, Clone, Default, Deserialize)]
+pub struct Inventory {
+    #[serde(default)]
+    pub hosts: Vec<Host>,
+}
+
+// a host's keys come from its keymanager api, a keystore directory(e.g. mounted or synced), or both
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Host {
+    pub name: String,
+    pub keymanager: Option<Endpoint>,
+    pub token_file: Option<String>,
+    pub keystores: Option<String>,
+    // validator client and its command line, for checking doppelganger protection on a remote host
+    pub client: Option<String>,
+    pub command: Option<String>,
+}
+
+impl Inventory {
+    pub fn from_file(path: &str) -> Result<Inventory> {
+        let contents = fs::read_to_string(path)?;
+        let inv: Inventory = serde_yaml::from_str(contents.as_str())?;
+        Ok(inv)
+    }
+}
+
+impl Host {
+    // a key in both the keymanager and the keystore directory is the same key on the same host
+    fn keys(&self) -> BTreeSet<String> {
+        let mut keys = BTreeSet::new();
+        if let Some(km) = &self.keymanager {
+            let mut km = km.clone();
+            if let Some(path) = &self.token_file {
+                match vc::read_token(path.as_str()) {
+                    Ok(t) => {
+                        km.headers.insert(String::from("Authorization"), format!("Bearer {}", t));
+                    },
+                    Err(e) => {
+                        let msg = Rezzy{ message: format!("{} keymanager token {} can't be read: {}", self.name, path, e) };
+                        msg.write_red();
+                    },
+                }
+            }
+            match vc::loaded_keys(&km) {
+                Ok(k) => keys.extend(k),
+                Err(e) => {
+                    let msg = Rezzy{ message: format!("Unable to list keys on {} from {}: {}", self.name, km.url, e) };
+                    msg.write_red();
+                },
+            }
+        }
+        if let Some(dir) = &self.keystores {
+            match keystore::keystore_files(Path::new(dir)) {
+                Ok(files) => keys.extend(files.iter().filter_map(|f| keystore::keystore_pubkey(f).ok())),
+                Err(e) => {
+                    let msg = Rezzy{ message: format!("Unable to read keystores on {} from {}: {}", self.name, dir, e) };
+                    msg.write_red();
+                },
+            }
+        }
+        keys
+    }
+    fn doppelganger_check(&self) {
+        match (self.client.as_deref().and_then(vc::parse_client), &self.command) {
+            (Some(client), Some(command)) => {
+                let cmd: Vec<String> = command.split_whitespace().map(String::from).collect();
+                vc::doppelganger_check(format!("{} {}", self.name, client).as_str(), client.as_str(), &cmd);
+            },
+            _ => {
+                let msg = Rezzy{ message: format!("doppelganger protection not checked on {}(add its client and command to the inventory)", self.name) };
+                msg.write_yellow();
+            },
+        }
+    }
+}
+
+// pubkey -> hosts for every key held by more than one host
+pub fn duplicates(held: &[(String, BTreeSet<String>)]) -> BTreeMap<String, Vec<String>> {
+    let mut owners: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (host, keys) in held {
+        for k in keys {
+            owners.entry(k.clone()).or_default().insert(host.clone());
+        }
+    }
+    owners.into_iter()
+        .filter(|(_, hosts)| hosts.len() > 1)
+        .map(|(k, hosts)| (k, hosts.into_iter().collect()))
+        .collect()
+}
+
+// `local` is the keymanager url and keys of the validator client valid8r is running next to, when it was checked
+pub fn duplicate_check(inventory: &Inventory, local: Option<(&str, &[String])>) {
+    for h in &inventory.hosts {
+        h.doppelganger_check();
+    }
+    let mut held: Vec<(String, BTreeSet<String>)> = inventory.hosts.iter()
+        .map(|h| (h.name.clone(), h.keys()))
+        .collect();
+    if let Some((url, keys)) = local {
+        // the local validator client may be listed in the inventory already
+        let listed = inventory.hosts.iter()
+            .any(|h| h.keymanager.as_ref().is_some_and(|k| k.url.trim_end_matches('/') == url.trim_end_matches('/')));
+        if !listed {
+            held.push((String::from("localhost"), keys.iter().cloned().collect()));
+        }
+    }
+
+    let total: usize = held.iter().map(|(_, k)| k.len()).sum();
+    let dups = duplicates(&held);
+    if dups.is_empty() {
+        let msg = Rezzy{ message: format!("No validator key is held more than once({} keys across {} hosts)", total, held.len()) };
+        msg.write_green();
+    }
+    for (key, hosts) in dups {
+        let msg = Rezzy{ message: format!("validator {} is loaded on {}: SLASHING RISK, remove all but one", short_key(key.as_str()), hosts.join(", ")) };
+        msg.write_red();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn duplicate_keys_across_hosts() {
+        let inv: Inventory = serde_yaml::from_str(r#"
+hosts:
+  - name: box-a
+    keymanager: { url: "https://box-a:5062" }
+    token_file: /secrets/box-a-token
+  - name: box-b
+    keystores: /mnt/box-b/validator_keys
+    client: teku
+    command: teku vc --doppelganger-detection-enabled=true
+"#).unwrap();
+        assert_eq!(inv.hosts[0].keymanager.as_ref().unwrap().url, "https://box-a:5062");
+        assert_eq!(inv.hosts[0].client, None);
+        assert_eq!(inv.hosts[1].keystores.as_deref(), Some("/mnt/box-b/validator_keys"));
+        assert_eq!(inv.hosts[1].command.as_deref(), Some("teku vc --doppelganger-detection-enabled=true"));
+
+        let k = |s: &str| String::from(s);
+        let held = vec![
+            (k("box-a"), vec![k("0x01"), k("0x02")].into_iter().collect()),
+            (k("box-b"), vec![k("0x02"), k("0x03")].into_iter().collect()),
+            (k("box-a"), vec![k("0x01")].into_iter().collect()),
+        ];
+        let dups = duplicates(&held);
+        assert_eq!(dups.len(), 1);
+        assert_eq!(dups["0x02"], vec![k("box-a"), k("box-b")]);
+    }
+
+    #[test]
+    fn keymanager_and_keystores_on_one_host() {
+        // a keymanager that answers every request with the same key
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut stream = stream;
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let body = r#"{"data":[{"validating_pubkey":"0xAA"}]}"#;
+                let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+            }
+        });
+        let dir = std::env::temp_dir().join(format!("valid8r-inventory-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("keystore-0.json"), r#"{"crypto":{},"pubkey":"aa"}"#).unwrap();
+        fs::write(dir.join("keystore-1.json"), r#"{"crypto":{},"pubkey":"bb"}"#).unwrap();
+
+        let host = Host{
+            name: String::from("box-a"),
+            keymanager: Some(Endpoint::new(url.as_str())),
+            token_file: None,
+            keystores: Some(dir.to_string_lossy().into_owned()),
+            client: None,
+            command: None,
+        };
+        let keys = host.keys();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(keys.iter().cloned().collect::<Vec<String>>(), vec![String::from("0xaa"), String::from("0xbb")]);
+        assert!(duplicates(&[(host.name, keys)]).is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use serde_json::Value;
+use anyhow::{anyhow, Result};
+use crate::output::Rezzy;
+use crate::validator::short_key;
+
+// keystores sit at most a few directories down(lighthouse nests them per pubkey)
+static MAX_DEPTH: usize = 3;
+
+// json that clients keep next to their keystores: nimbus web3signer keys and prysm's wallet
+static NOT_KEYSTORES: [&str; 2] = ["remote_keystore.json", "all-accounts.keystore.json"];
+
+// every .json file under `dir` that could be an EIP-2335 keystore, deposit data and other client files excluded
+pub fn keystore_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    walk(dir, 0, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn walk(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && depth < MAX_DEPTH {
+            walk(&path, depth + 1, files)?;
+        } else if path.extension().is_some_and(|e| e == "json") {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let contents = fs::read_to_string(&path).unwrap_or_default();
+            if is_keystore(name.as_str(), contents.as_str()) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(())
+}
+
+// json without a crypto object isn't a keystore, json that doesn't parse is kept so it gets reported
+fn is_keystore(name: &str, contents: &str) -> bool {
+    if name.starts_with("deposit_data") || NOT_KEYSTORES.contains(&name) {
+        return false;
+    }
+    match serde_json::from_str::<Value>(contents) {
+        Ok(j) => j.get("crypto").is_some(),
+        Err(_) => true,
+    }
+}
+
+// 0x prefixed pubkey a keystore is for
+pub fn keystore_pubkey(path: &Path) -> Result<String> {
+    let j: serde_json::Value = serde_json::from_str(fs::read_to_string(path)?.as_str())?;
+    let key = j["pubkey"].as_str().ok_or_else(|| anyhow!("{} has no pubkey", path.display()))?;
+    Ok(format!("0x{}", key.trim_start_matches("0x").to_lowercase()))
+}
+
+// the EIP-2335 layout, kdf/checksum/cipher params differ by function so they stay loosely typed
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Keystore {
+    pub crypto: Crypto,
+    pub description: Option<String>,
+    pub pubkey: Option<String>,
+    pub path: String,
+    pub uuid: String,
+    pub version: u64,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Crypto {
+    pub kdf: Module,
+    pub checksum: Module,
+    pub cipher: Module,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Module {
+    pub function: String,
+    pub params: Value,
+    pub message: String,
+}
+
+// the work factors the EIP-2335 test vectors and deposit cli use, anything less is cheaper to brute force
+static SCRYPT_MIN_N: u64 = 262144;
+static PBKDF2_MIN_C: u64 = 262144;
+
+fn is_hex(s: &str, bytes: Option<usize>) -> bool {
+    let s = s.trim_start_matches("0x");
+    !s.is_empty() && s.len() % 2 == 0 && bytes.is_none_or(|b| s.len() == b * 2) && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_uuid(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    parts.iter().map(|p| p.len()).eq([8, 4, 4, 4, 12]) && parts.iter().all(|p| p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// EIP-2334 signing keys are m/12381/3600/<i>/0/0, m/12381/3600/<i>/0 is the withdrawal key
+pub fn key_path(path: &str) -> Option<(u64, bool)> {
+    let parts: Vec<&str> = path.split('/').collect();
+    if parts.len() < 5 || parts[..3] != ["m", "12381", "3600"] || parts[4] != "0" {
+        return None;
+    }
+    let index = parts[3].parse().ok()?;
+    match parts.len() {
+        5 => Some((index, false)),
+        6 if parts[5] == "0" => Some((index, true)),
+        _ => None,
+    }
+}
+
+impl Keystore {
+    pub fn from_file(path: &Path) -> Result<Keystore> {
+        let ks: Keystore = serde_json::from_str(fs::read_to_string(path)?.as_str())?;
+        Ok(ks)
+    }
+    // anything that keeps a validator client from decrypting or loading the keystore
+    pub fn errors(&self) -> Vec<String> {
+        let mut errs = Vec::new();
+        if self.version != 4 {
+            errs.push(format!("version is {}, EIP-2335 keystores are version 4", self.version));
+        }
+        if !is_uuid(self.uuid.as_str()) {
+            errs.push(format!("uuid {:?} is not a UUID", self.uuid));
+        }
+        match &self.pubkey {
+            Some(k) if !is_hex(k, Some(48)) => errs.push(String::from("pubkey is not a 48 byte hex string")),
+            None => errs.push(String::from("has no pubkey, validator clients can't tell which validator it is")),
+            _ => (),
+        }
+        let derivation = self.path.strip_prefix("m/").map(|p| p.split('/').all(|i| i.parse::<u32>().is_ok()));
+        if !self.path.is_empty() && derivation != Some(true) {
+            errs.push(format!("path {:?} is not a derivation path", self.path));
+        }
+
+        let kdf = &self.crypto.kdf;
+        let param = |k: &str| kdf.params[k].as_u64();
+        match kdf.function.as_str() {
+            "scrypt" => {
+                if param("dklen") != Some(32) {
+                    errs.push(String::from("scrypt dklen must be 32"));
+                }
+                if !param("n").is_some_and(|n| n > 1 && n.is_power_of_two()) {
+                    errs.push(String::from("scrypt n must be a power of two"));
+                }
+                if param("r").is_none() || param("p").is_none() {
+                    errs.push(String::from("scrypt r and p are missing"));
+                }
+            },
+            "pbkdf2" => {
+                if param("dklen") != Some(32) {
+                    errs.push(String::from("pbkdf2 dklen must be 32"));
+                }
+                if param("c").is_none() {
+                    errs.push(String::from("pbkdf2 c is missing"));
+                }
+                if kdf.params["prf"].as_str() != Some("hmac-sha256") {
+                    errs.push(String::from("pbkdf2 prf must be hmac-sha256"));
+                }
+            },
+            f => errs.push(format!("kdf function {:?} is not scrypt or pbkdf2", f)),
+        }
+        if !kdf.params["salt"].as_str().is_some_and(|s| is_hex(s, None)) {
+            errs.push(String::from("kdf salt is not a hex string"));
+        }
+        if !kdf.message.is_empty() {
+            errs.push(String::from("kdf message must be empty"));
+        }
+
+        let checksum = &self.crypto.checksum;
+        if checksum.function != "sha256" || !is_hex(checksum.message.as_str(), Some(32)) {
+            errs.push(String::from("checksum must be a sha256 with a 32 byte message"));
+        }
+        let cipher = &self.crypto.cipher;
+        if cipher.function != "aes-128-ctr" {
+            errs.push(format!("cipher function {:?} is not aes-128-ctr", cipher.function));
+        }
+        if !cipher.params["iv"].as_str().is_some_and(|iv| is_hex(iv, Some(16))) {
+            errs.push(String::from("cipher iv is not a 16 byte hex string"));
+        }
+        if !is_hex(cipher.message.as_str(), Some(32)) {
+            errs.push(String::from("cipher message is not an encrypted 32 byte secret key"));
+        }
+        errs
+    }
+    // loadable, but not the way it should be
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warns = Vec::new();
+        let params = &self.crypto.kdf.params;
+        match self.crypto.kdf.function.as_str() {
+            "scrypt" if params["n"].as_u64().is_some_and(|n| n < SCRYPT_MIN_N) => {
+                warns.push(format!("scrypt n is {}, below {} a stolen keystore is cheap to brute force", params["n"], SCRYPT_MIN_N));
+            },
+            "pbkdf2" if params["c"].as_u64().is_some_and(|c| c < PBKDF2_MIN_C) => {
+                warns.push(format!("pbkdf2 c is {}, below {} a stolen keystore is cheap to brute force", params["c"], PBKDF2_MIN_C));
+            },
+            _ => (),
+        }
+        match key_path(self.path.as_str()) {
+            Some((i, false)) => warns.push(format!("is the WITHDRAWAL key for index {}({}), it doesn't belong on a validator host", i, self.path)),
+            None if self.path.is_empty() => warns.push(String::from("has no derivation path(imported key)")),
+            None => warns.push(format!("path {} is not an EIP-2334 validator signing key path", self.path)),
+            _ => (),
+        }
+        warns
+    }
+}
+
+fn access_check(file: &Path, meta: &fs::Metadata) {
+    let mode = meta.permissions().mode() & 0o777;
+    if mode & 0o007 != 0 {
+        let msg = Rezzy{ message: format!("{} is WORLD accessible(mode {:o}), chmod 600 it", file.display(), mode) };
+        msg.write_red();
+    } else if mode & 0o070 != 0 {
+        let msg = Rezzy{ message: format!("{} is group accessible(mode {:o}), prefer 600", file.display(), mode) };
+        msg.write_yellow();
+    }
+}
+
+// `vc_uid` is the user the validator client runs as, when it is running
+pub fn keystore_check(dir: &str, vc_uid: Option<u32>) {
+    let files = match keystore_files(Path::new(dir)) {
+        Ok(f) => f,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to read keystores from {}: {}", dir, e) };
+            msg.write_red();
+            return;
+        },
+    };
+    if files.is_empty() {
+        let msg = Rezzy{ message: format!("No keystores found in {}", dir) };
+        msg.write_yellow();
+        return;
+    }
+
+    let mut valid = 0;
+    let mut pubkeys: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    let mut uuids: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    let mut owners: BTreeMap<u32, usize> = BTreeMap::new();
+    for f in &files {
+        if let Ok(meta) = fs::metadata(f) {
+            access_check(f, &meta);
+            *owners.entry(meta.uid()).or_default() += 1;
+        }
+
+        let ks = match Keystore::from_file(f) {
+            Ok(ks) => ks,
+            Err(e) => {
+                let msg = Rezzy{ message: format!("{} is NOT an EIP-2335 keystore: {}", f.display(), e) };
+                msg.write_red();
+                continue;
+            },
+        };
+        let errs = ks.errors();
+        for e in &errs {
+            let msg = Rezzy{ message: format!("{}: {}", f.display(), e) };
+            msg.write_red();
+        }
+        for w in ks.warnings() {
+            let msg = Rezzy{ message: format!("{} {}", f.display(), w) };
+            msg.write_yellow();
+        }
+        if errs.is_empty() {
+            valid += 1;
+        }
+        if let Some(k) = &ks.pubkey {
+            pubkeys.entry(k.trim_start_matches("0x").to_lowercase()).or_default().push(f.clone());
+        }
+        uuids.entry(ks.uuid.to_lowercase()).or_default().push(f.clone());
+    }
+
+    let msg = Rezzy{ message: format!("{} of {} keystores in {} are well formed", valid, files.len(), dir) };
+    if valid == files.len() {
+        msg.write_green();
+    } else {
+        msg.write_red();
+    }
+
+    for (k, paths) in pubkeys.iter().filter(|(_, p)| p.len() > 1) {
+        let list: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+        let msg = Rezzy{ message: format!("validator {} has {} keystores({}), a client may load it twice", short_key(format!("0x{}", k).as_str()), paths.len(), list.join(", ")) };
+        msg.write_red();
+    }
+    for (u, paths) in uuids.iter().filter(|(_, p)| p.len() > 1) {
+        let msg = Rezzy{ message: format!("uuid {} is shared by {} keystores", u, paths.len()) };
+        msg.write_yellow();
+    }
+
+    if owners.contains_key(&0) {
+        let msg = Rezzy{ message: format!("{} keystores are owned by root, run the validator client as an unprivileged user that owns them", owners[&0]) };
+        msg.write_yellow();
+    } else if owners.len() > 1 {
+        let msg = Rezzy{ message: format!("keystores in {} have {} different owners(uids {:?})", dir, owners.len(), owners.keys().collect::<Vec<_>>()) };
+        msg.write_yellow();
+    }
+    if let Some(uid) = vc_uid {
+        let foreign = owners.iter().filter(|(o, _)| **o != uid).map(|(_, n)| n).sum::<usize>();
+        if uid != 0 && foreign > 0 {
+            let msg = Rezzy{ message: format!("{} keystores aren't owned by the validator client's user(uid {})", foreign, uid) };
+            msg.write_yellow();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keystore_structure() {
+        // EIP-2335 scrypt test vector
+        let mut ks: Keystore = serde_json::from_str(r#"{
+            "crypto": {
+                "kdf": {"function": "scrypt", "params": {"dklen": 32, "n": 262144, "p": 1, "r": 8, "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"}, "message": ""},
+                "checksum": {"function": "sha256", "params": {}, "message": "d2217fe5f3e9a1e34581ef8a78f7c9928e436d36dacc5e846690a5581e8ea484"},
+                "cipher": {"function": "aes-128-ctr", "params": {"iv": "264daa3f303d7259501c93d997d84fe6"}, "message": "06ae90d55fe0a6e9c5c3bc5b170827b2e5cce3929ed3f116c2811e6366dfe20f"}
+            },
+            "description": "This is a test keystore that uses scrypt to secure the secret.",
+            "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+            "path": "m/12381/60/3141592653/589793238",
+            "uuid": "1d85ae20-35c5-4611-98e8-aa14a633906f",
+            "version": 4
+        }"#).unwrap();
+        assert!(ks.errors().is_empty());
+        assert_eq!(ks.warnings().len(), 1);
+
+        ks.path = String::from("m/12381/3600/7/0/0");
+        assert!(ks.errors().is_empty());
+        assert!(ks.warnings().is_empty());
+        assert_eq!(key_path("m/12381/3600/7/0"), Some((7, false)));
+
+        ks.version = 3;
+        ks.crypto.kdf.params["n"] = serde_json::json!(1000);
+        assert_eq!(ks.errors().len(), 2);
+        ks.crypto.kdf.params["n"] = serde_json::json!(1024);
+        assert_eq!(ks.errors().len(), 1);
+        assert_eq!(ks.warnings().len(), 1);
+
+        assert!(is_keystore("keystore-m_12381_3600_0_0_0.json", r#"{"crypto": {}}"#));
+        assert!(is_keystore("voting-keystore.json", "{not json"));
+        assert!(!is_keystore("remote_keystore.json", r#"{"version": 1, "type": "web3signer"}"#));
+        assert!(!is_keystore("all-accounts.keystore.json", r#"{"crypto": {}}"#));
+        assert!(!is_keystore("slashing_protection.json", r#"{"metadata": {}, "data": []}"#));
+        assert!(!is_keystore("deposit_data-1700000000.json", "[]"));
+    }
+}
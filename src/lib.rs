@@ -2,21 +2,38 @@ use sysinfo::{System, SystemExt, DiskExt};
 use std::net::TcpListener;
 use std::io::ErrorKind;
 use std::process;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
 use std::error::{Error as Err};
 use structopt::StructOpt;
 use chrono::prelude::*;
 use output::Rezzy;
 use eth2::*;
+use network::{Network, NetworkName};
+use reference::{Endpoint, References};
+use settings::Settings;
+use inventory::Inventory;
 
 mod output;
 mod eth1;
 mod eth2;
+mod docker;
+mod network;
+mod reference;
+mod settings;
+mod engine;
+mod validator;
+mod vc;
+mod keystore;
+mod inventory;
+mod slashing;
+mod deposit;
 
 #[derive(StructOpt)]
 pub struct Config {
-    // ethereum 1.0 client 
+    // ethereum 1.0 client, required unless running a subcommand
     #[structopt(short = "1", long)]
-    pub eth1: String,
+    pub eth1: Option<String>,
 
     // optional: ethereum 1.0 client listener port
     #[structopt(long)]
@@ -26,9 +43,17 @@ pub struct Config {
     #[structopt(long)]
     pub eth1_http_port: Option<i32>,
 
-    // ethereum 2.0 client 
+    // optional: ethereum 1.0 client engine api port
+    #[structopt(long)]
+    pub eth1_engine_port: Option<i32>,
+
+    // optional: jwt secret shared by the eth1 and eth2 clients, found from their command lines otherwise
+    #[structopt(long)]
+    pub jwt_secret: Option<String>,
+
+    // ethereum 2.0 client, required unless running a subcommand
     #[structopt(short = "2", long)]
-    pub eth2: String,
+    pub eth2: Option<String>,
 
     // optional: ethereum 2.0 client listener port
     #[structopt(long)]
@@ -38,17 +63,166 @@ pub struct Config {
     #[structopt(long)]
     pub eth2_http_port: Option<i32>,
 
-    // optional: testnet bool flag
-    #[structopt(short = "t", long)]
+    // optional: network to validate against, defaults to mainnet
+    #[structopt(long, default_value = "mainnet", possible_values = &NetworkName::variants(), case_insensitive = true)]
+    pub network: NetworkName,
+
+    // deprecated: alias for --network
+    #[structopt(short = "t", long, hidden = true)]
     pub testnet: Option<String>,
 
+    // optional: custom devnet definition(yaml/json), requires --network custom
+    #[structopt(long)]
+    pub network_file: Option<String>,
+
     // optional: ntp endpoint 
     #[structopt(short = "n", long)]
     pub ntp_endpoint: Option<String>,
 
-    // optional: infura endpoint
-    #[structopt(short = "i", long)]
+    // optional: valid8r config file(yaml/json)
+    #[structopt(long)]
+    pub config: Option<String>,
+
+    // optional: reference execution endpoint(s) to compare the local head against
+    #[structopt(long)]
+    pub eth1_reference: Vec<String>,
+
+    // deprecated: alias for --eth1-reference
+    #[structopt(short = "i", long, hidden = true)]
     pub infura_endpoint: Option<String>,
+
+    // optional: reference beacon endpoint(s) to compare the local head against
+    #[structopt(long)]
+    pub eth2_reference: Vec<String>,
+
+    // optional: number of references that must agree before valid8r trusts them
+    #[structopt(long)]
+    pub reference_quorum: Option<usize>,
+
+    // optional: skip all reference endpoint comparisons
+    #[structopt(long)]
+    pub no_reference: bool,
+
+    // optional: blocks/slots behind the references before warning
+    #[structopt(long, default_value = "2")]
+    pub block_lag_warn: u64,
+
+    // optional: blocks/slots behind the references before failing
+    #[structopt(long, default_value = "10")]
+    pub block_lag_fail: u64,
+
+    // optional: epochs finality may trail the head before failing
+    #[structopt(long, default_value = "4")]
+    pub finality_lag: u64,
+
+    // optional: validator pubkey(s) or index(es) to check
+    #[structopt(long)]
+    pub validator: Vec<String>,
+
+    // optional: file of validator pubkeys/indices, one per line
+    #[structopt(long)]
+    pub validator_file: Option<String>,
+
+    // optional: epochs of attestation rewards and balance history to check validators over
+    #[structopt(long, default_value = "3")]
+    pub validator_epochs: u64,
+
+    // optional: epochs ahead a block proposal makes maintenance unsafe, at most 2(the current and next epoch)
+    #[structopt(long, default_value = "2")]
+    pub duty_warn_epochs: u64,
+
+    // optional: validator client(lighthouse, prysm, teku, nimbus, lodestar)
+    #[structopt(long)]
+    pub vc: Option<String>,
+
+    // optional: validator client keymanager api url
+    #[structopt(long)]
+    pub keymanager_url: Option<String>,
+
+    // optional: file holding the keymanager api bearer token
+    #[structopt(long)]
+    pub keymanager_token: Option<String>,
+
+    // optional: validator keys directory of EIP-2335 keystores to check
+    #[structopt(long)]
+    pub keystores: Option<String>,
+
+    // optional: inventory of validator client hosts to check for duplicate keys
+    #[structopt(long)]
+    pub inventory: Option<String>,
+
+    // optional: docker-compose file the clients are deployed with
+    #[structopt(short = "c", long)]
+    pub compose_file: Option<String>,
+
+    #[structopt(subcommand)]
+    pub cmd: Option<Command>,
+}
+
+// standalone checks that only need the network, not running clients
+#[derive(StructOpt)]
+pub enum Command {
+    // lint an EIP-3076 slashing protection interchange file before importing it
+    #[structopt(name = "slashing-db")]
+    SlashingDb {
+        file: String,
+    },
+    // check a staking deposit cli deposit_data-*.json before sending any deposits
+    #[structopt(name = "deposit-data")]
+    DepositData {
+        file: String,
+    },
+}
+
+// the network to validate against, exits on a bad --network/--network-file pair
+fn network(cfg: &Config) -> Network {
+    let name = match &cfg.testnet {
+        Some(t) => match t.parse::<NetworkName>() {
+            Ok(n) => {
+                println!("WARNING: --testnet is deprecated, use --network {}", t.to_lowercase());
+                n
+            },
+            Err(_) => {
+                println!("ERROR: --testnet is deprecated and {} is not a supported network, use --network({})", t, NetworkName::variants().join(", "));
+                process::exit(1);
+            },
+        },
+        None => cfg.network,
+    };
+    match (name, &cfg.network_file) {
+        (NetworkName::CUSTOM, Some(path)) => match Network::from_file(path.as_str()) {
+            Ok(n) => n,
+            Err(e) => {
+                println!("ERROR: Could not read network file {}: {}", path, e);
+                process::exit(1);
+            },
+        },
+        (NetworkName::CUSTOM, None) => {
+            println!("ERROR: --network custom requires a --network-file");
+            process::exit(1);
+        },
+        (name, Some(_)) => {
+            println!("ERROR: --network-file can only be used with --network custom(entered {:?})", name);
+            process::exit(1);
+        },
+        (name, None) => Network::new(name).unwrap(),
+    }
+}
+
+pub fn command(cfg: &Config, cmd: &Command) {
+    let net = network(cfg);
+    match cmd {
+        Command::SlashingDb { file } => {
+            if !slashing::slashing_db_check(file.as_str(), &net) {
+                process::exit(1);
+            }
+        },
+        Command::DepositData { file } => {
+            if !deposit::deposit_data_check(file.as_str(), &net) {
+                process::exit(1);
+            }
+        },
+    }
 }
 
 #[derive(Debug,PartialEq)]
@@ -65,40 +239,163 @@ pub struct Valid8r {
     pub eth1: eth1::Eth1Client,
     pub eth1_listener_addr: String,
     pub eth1_http_addr: String,
+    pub eth1_engine_addr: String,
+    pub jwt_secret: Option<String>,
     pub eth2: Eth2Client,
     pub eth2_listener_addr: String,
     pub eth2_http_addr: String,
     pub ntp_endpoint: String,
+    pub network: Network,
+    pub eth2_references: References,
+    pub finality_lag: u64,
+    pub validators: Vec<String>,
+    pub validator_epochs: u64,
+    pub duty_warn_epochs: u64,
+    pub vc: Option<String>,
+    pub keymanager_url: Option<String>,
+    pub keymanager_token: Option<String>,
+    pub keystores: Option<String>,
+    pub inventory: Option<Inventory>,
+    pub settings: Settings,
+    pub eth1_service: Option<docker::ComposeService>,
+    pub eth2_service: Option<docker::ComposeService>,
 }
 
 impl Valid8r {
     pub fn new(cfg: Config) -> Valid8r {
+        if cfg.eth1.is_none() || cfg.eth2.is_none() {
+            println!("ERROR: --eth1 and --eth2 are required to check a node");
+            process::exit(1);
+        }
+        if cfg.duty_warn_epochs > validator::MAX_DUTY_EPOCHS {
+            println!("ERROR: --duty-warn-epochs can be at most {}, beacon nodes only know proposers for the current and next epoch", validator::MAX_DUTY_EPOCHS);
+            process::exit(1);
+        }
+        let net = network(&cfg);
+        let mut settings = match &cfg.config {
+            Some(path) => match Settings::from_file(path.as_str()) {
+                Ok(s) => s,
+                Err(e) => {
+                    println!("ERROR: Could not read config file {}: {}", path, e);
+                    process::exit(1);
+                },
+            },
+            None => Settings::default(),
+        };
+
+        let mut withdrawal_addresses = Vec::new();
+        for a in &settings.withdrawal_addresses {
+            match validator::parse_address(a.as_str()) {
+                Ok(a) => withdrawal_addresses.push(a),
+                Err(e) => {
+                    println!("ERROR: Invalid withdrawal address in {}: {}", cfg.config.as_deref().unwrap_or_default(), e);
+                    process::exit(1);
+                },
+            }
+        }
+        settings.withdrawal_addresses = withdrawal_addresses;
+        if let Some(a) = &settings.fee_recipient {
+            match validator::parse_address(a.as_str()) {
+                Ok(a) => settings.fee_recipient = Some(a),
+                Err(e) => {
+                    println!("ERROR: Invalid fee recipient in {}: {}", cfg.config.as_deref().unwrap_or_default(), e);
+                    process::exit(1);
+                },
+            }
+        }
+
+        // flags beat the config file, which beats the network's public defaults
+        let references = |flags: &[String], file: &Option<Vec<Endpoint>>, defaults: &[Endpoint]| -> References {
+            let endpoints = if cfg.no_reference {
+                Vec::new()
+            } else if !flags.is_empty() {
+                flags.iter().map(|u| Endpoint::new(u.as_str())).collect()
+            } else {
+                file.clone().unwrap_or_else(|| defaults.to_vec())
+            };
+            References{
+                endpoints,
+                quorum: cfg.reference_quorum.or(settings.references.quorum).unwrap_or(1),
+                lag_warn: cfg.block_lag_warn,
+                lag_fail: cfg.block_lag_fail,
+            }
+        };
+        // validators from flags, the validator file and the config file, each named once
+        let mut ids = cfg.validator.clone();
+        ids.extend(settings.validators.iter().cloned());
+        if let Some(path) = &cfg.validator_file {
+            match validator::load_ids(path.as_str()) {
+                Ok(v) => ids.extend(v),
+                Err(e) => {
+                    println!("ERROR: Could not read validator file {}: {}", path, e);
+                    process::exit(1);
+                },
+            }
+        }
+        let mut validators: Vec<String> = Vec::new();
+        for id in ids {
+            match validator::parse_id(id.as_str()) {
+                Ok(v) if !validators.contains(&v) => validators.push(v),
+                Ok(_) => (),
+                Err(e) => {
+                    println!("ERROR: Invalid validator: {}", e);
+                    process::exit(1);
+                },
+            }
+        }
+
+        let inventory = cfg.inventory.as_ref().map(|path| match Inventory::from_file(path.as_str()) {
+            Ok(i) => i,
+            Err(e) => {
+                println!("ERROR: Could not read inventory file {}: {}", path, e);
+                process::exit(1);
+            },
+        });
+
+        let mut eth1_flags = cfg.eth1_reference.clone();
+        if let Some(infura) = &cfg.infura_endpoint {
+            println!("WARNING: --infura-endpoint is deprecated, use --eth1-reference {}", infura);
+            eth1_flags.push(infura.clone());
+        }
+        let eth1_references = references(&eth1_flags, &settings.references.execution, &net.eth1_references);
+        let eth2_references = references(&cfg.eth2_reference, &settings.references.beacon, &net.eth2_references);
+
         let mut v = Valid8r{
             eth1: eth1::Eth1Client::new(
                 String::from("GETH"),
                 String::from("http://127.0.0.1:8545"),
-                String::from("https://mainnet.infura.io/v3/65daaf22efb6473e8b56161095669ca8"),
-                false)
+                eth1_references,
+                net.clone())
             ,
-            eth1_listener_addr: String::from("0.0.0.0:30303"),
+            eth1_listener_addr: format!("0.0.0.0:{}", net.eth1_p2p_port),
             eth1_http_addr: String::from("127.0.0.1:8545"),
+            eth1_engine_addr: String::from("127.0.0.1:8551"),
+            jwt_secret: cfg.jwt_secret,
             eth2: Eth2Client::NONE,
-            eth2_listener_addr: String::from("0.0.0.0:9000"),
+            eth2_listener_addr: format!("0.0.0.0:{}", net.eth2_p2p_port),
             eth2_http_addr: String::from("0.0.0.0:5052"),
             ntp_endpoint: String::from("0.pool.ntp.org:123"),
+            network: net,
+            eth2_references,
+            finality_lag: cfg.finality_lag,
+            validators,
+            validator_epochs: cfg.validator_epochs,
+            duty_warn_epochs: cfg.duty_warn_epochs,
+            vc: None,
+            keymanager_url: cfg.keymanager_url,
+            keymanager_token: cfg.keymanager_token,
+            keystores: cfg.keystores.clone(),
+            inventory,
+            settings,
+            eth1_service: None,
+            eth2_service: None,
         };
 
         if let Some(ntp) = cfg.ntp_endpoint {
             v.ntp_endpoint = ntp;
         }        
-        if let Some(infura) = cfg.infura_endpoint {
-            v.eth1.infura_addr = infura;
-        }
-        if let Some(_) = cfg.testnet {
-            v.eth1.testnet = true;
-        }
 
-        let e1: &str = &cfg.eth1.to_lowercase();
+        let e1: &str = &cfg.eth1.as_deref().unwrap_or_default().to_lowercase();
         match e1 {
             "geth" => v.eth1.name = String::from("GETH"),
             "besu" => v.eth1.name = String::from("BESU"),
@@ -110,7 +407,7 @@ impl Valid8r {
             },
         }
 
-        let e2: &str = &cfg.eth2.to_lowercase();
+        let e2: &str = &cfg.eth2.as_deref().unwrap_or_default().to_lowercase();
         match e2 {
             "lighthouse" => v.eth2 = Eth2Client::LIGHTHOUSE,
             "prysm" => {
@@ -129,6 +426,16 @@ impl Valid8r {
             },
         }
 
+        if let Some(name) = &cfg.vc {
+            match vc::parse_client(name.as_str()) {
+                Some(c) => v.vc = Some(c),
+                None => {
+                    println!("ERROR: Please input a valid validator client(entered {})", name);
+                    process::exit(1);
+                },
+            }
+        }
+
         if let Some(port) = cfg.eth1_listener_port {
             v.eth1_listener_addr = format!("0.0.0.0:{}", port);
         }
@@ -136,24 +443,53 @@ impl Valid8r {
             v.eth1_http_addr = format!("127.0.0.1:{}", port);
             v.eth1.http_addr = format!("http://127.0.0.1:{}", port);
         }
+        if let Some(port) = cfg.eth1_engine_port {
+            v.eth1_engine_addr = format!("127.0.0.1:{}", port);
+        }
         if let Some(port) = cfg.eth2_listener_port {
             v.eth2_listener_addr = format!("0.0.0.0:{}", port);
         }
-        if let Some(port) = cfg.eth1_http_port {
+        if let Some(port) = cfg.eth2_http_port {
             v.eth2_http_addr = format!("127.0.0.1:{}", port);
         }
 
+        // clients deployed with compose listen on whatever host ports they're published on
+        if let Some(path) = cfg.compose_file {
+            let services = match docker::load_compose(path.as_str()) {
+                Ok(s) => s,
+                Err(e) => {
+                    println!("ERROR: Could not read compose file {}: {}", path, e);
+                    process::exit(1);
+                },
+            };
+            v.eth1_service = docker::find_service(&services, v.eth1.name.as_str());
+            v.eth2_service = docker::find_service(&services, format!("{:?}", v.eth2).as_str());
+        }
+        if let Some(svc) = &v.eth1_service {
+            v.eth1_listener_addr = docker::remap_addr(&v.eth1_listener_addr, svc);
+            v.eth1_http_addr = docker::remap_addr(&v.eth1_http_addr, svc);
+            v.eth1_engine_addr = docker::remap_addr(&v.eth1_engine_addr, svc);
+            v.eth1.http_addr = format!("http://{}", v.eth1_http_addr);
+        }
+        if let Some(svc) = &v.eth2_service {
+            v.eth2_listener_addr = docker::remap_addr(&v.eth2_listener_addr, svc);
+            v.eth2_http_addr = docker::remap_addr(&v.eth2_http_addr, svc);
+        }
 
         v
     }
     pub fn run(&self) -> Result<(), Box<dyn Err>> {    
-        let banner = Rezzy{ message: format!("Valid8r is Valid8ing your Valid8r") };
+        let banner = Rezzy{ message: String::from("Valid8r is Valid8ing your Valid8r") };
         banner.bold();
 
         self.sys_req();
 
+        self.docker_req();
+
         self.net_req();
 
+        self.engine_req();
+
         if let Err(_e) = self.eth1.eth1_check() {
             let msg = Rezzy{ message: format!("VALID8R could not connect to {} at addr {}", self.eth1.name, self.eth1.http_addr) };
             msg.write_red();
@@ -161,115 +497,178 @@ impl Valid8r {
 
         match self.eth2 {
             Eth2Client::LIGHTHOUSE => {
-                if let Err(_e) = eth2_check("LIGHTHOUSE", format!("http://{}", self.eth2_http_addr)) {
-                    let msg = Rezzy{ message: format!("VALID8R could not connect to LIGHTHOUSE") };
+                if let Err(_e) = eth2_check("LIGHTHOUSE", format!("http://{}", self.eth2_http_addr), &self.network, &self.eth2_references, self.finality_lag) {
+                    let msg = Rezzy{ message: String::from("VALID8R could not connect to LIGHTHOUSE") };
                     msg.write_red();
                 }
             }
             Eth2Client::PRYSM => {
-                if let Err(_e) = eth2_check("PRYSM", format!("http://{}", self.eth2_http_addr)) {
-                    let msg = Rezzy{ message: format!("VALID8R ERROR could not connect to PRYSM") };
+                if let Err(_e) = eth2_check("PRYSM", format!("http://{}", self.eth2_http_addr), &self.network, &self.eth2_references, self.finality_lag) {
+                    let msg = Rezzy{ message: String::from("VALID8R ERROR could not connect to PRYSM") };
                     msg.write_red();
                 }
             },
             Eth2Client::NIMBUS => {
-                if let Err(_e) = eth2_check("NIMBUS", format!("http://{}", self.eth2_http_addr)) {
-                    let msg = Rezzy{ message: format!("VALID8R ERROR could not connect to NIMBUS") };
+                if let Err(_e) = eth2_check("NIMBUS", format!("http://{}", self.eth2_http_addr), &self.network, &self.eth2_references, self.finality_lag) {
+                    let msg = Rezzy{ message: String::from("VALID8R ERROR could not connect to NIMBUS") };
                     msg.write_red();
                 }
             },
             Eth2Client::TEKU => {
-                if let Err(_e) = eth2_check("TEKU",format!("http://{}", self.eth2_http_addr)) {
-                    let msg = Rezzy{ message: format!("VALID8R ERROR could not connect to TEKU") };
+                if let Err(_e) = eth2_check("TEKU",format!("http://{}", self.eth2_http_addr), &self.network, &self.eth2_references, self.finality_lag) {
+                    let msg = Rezzy{ message: String::from("VALID8R ERROR could not connect to TEKU") };
                     msg.write_red();
                 }
             },
             _ => println!("can't happen")
         }
 
+        if self.eth2 != Eth2Client::NONE {
+            self.link_req();
+        }
+
+        // with no validators configured, check the ones the validator client has loaded
+        let loaded = self.vc_req();
+        if let Some(dir) = &self.keystores {
+            self.keystore_req(dir);
+        }
+        if let Some(inv) = &self.inventory {
+            self.duplicate_req(inv, loaded.as_ref());
+        }
+        let validators = match loaded {
+            Some(keys) if self.validators.is_empty() => keys,
+            _ => self.validators.clone(),
+        };
+        if self.eth2 != Eth2Client::NONE && !validators.is_empty() {
+            self.validator_req(&validators);
+        }
+
         println!("\n");
         
         Ok(())
     }
-    pub fn net_req(&self) {
-        let banner = Rezzy{ message: format!("\nNetwork Requirements:") };
+    pub fn docker_req(&self) {
+        let banner = Rezzy{ message: String::from("\nContainer Deployment:") };
         banner.bold();
-        match self.eth1 {
-            _ => {
-                match TcpListener::bind(&self.eth1_listener_addr) {
-                    Ok(_) => {
-                        let msg = Rezzy{ message: format!("{} IS NOT LISTENING ON PORT: {}", self.eth1.name, self.eth1_listener_addr) };
-                        msg.write_red();
-                    },
-                    Err(e) => {
-                        if e.kind() == ErrorKind::AddrInUse {
-                            let msg = Rezzy{ message: format!("{} is listening on port: {}", self.eth1.name, self.eth1_listener_addr) };
-                            msg.write_green();
-                        } else {
-                            let msg = Rezzy{ message: format!("{:?} misc error when listening on {}", e, self.eth1_listener_addr) };
-                            msg.write_yellow();
-                        }
-                    }
-                }
-                match TcpListener::bind(&self.eth1_http_addr) {
-                    Ok(_) => {
-                        let msg = Rezzy{ message: format!("{} IS NOT LISTENING for JSON RPC on PORT: {}", self.eth1.name, self.eth1_http_addr) };
-                        msg.write_red();
-                    },
-                    Err(e) => {
-                        if e.kind() == ErrorKind::AddrInUse {
-                            let msg = Rezzy{ message: format!("{} is listening on port: {}", self.eth1.name, self.eth1_http_addr) };
-                            msg.write_green();
-                        } else {
-                            let msg = Rezzy{ message: format!("{:?} misc error when listening on {}", e, self.eth1_http_addr) };
-                            msg.write_yellow();
-                        }
-                    }
-                }
+
+        // addrs have already been remapped to host ports, checks are reported against the container side
+        let container_ports = |addrs: &[&String], svc: &Option<docker::ComposeService>| -> Vec<u16> {
+            addrs.iter()
+                .filter_map(|a| docker::addr_port(a))
+                .map(|p| svc.as_ref().map_or(p, |s| s.container_port(p)))
+                .collect()
+        };
+        let eth1_ports = container_ports(&[&self.eth1_listener_addr, &self.eth1_http_addr, &self.eth1_engine_addr], &self.eth1_service);
+        docker::docker_check(self.eth1.name.as_str(), self.eth1_service.as_ref(), &eth1_ports);
+
+        let eth2_ports = container_ports(&[&self.eth2_listener_addr, &self.eth2_http_addr], &self.eth2_service);
+        docker::docker_check(format!("{:?}", self.eth2).as_str(), self.eth2_service.as_ref(), &eth2_ports);
+    }
+    pub fn duplicate_req(&self, inventory: &Inventory, loaded: Option<&Vec<String>>) {
+        let banner = Rezzy{ message: format!("\nDuplicate Keys: {} hosts", inventory.hosts.len()) };
+        banner.bold();
+
+        let url = self.vc.as_ref().map(|c| self.keymanager_url.clone().unwrap_or_else(|| vc::default_keymanager(c.as_str())));
+        let local = match (&url, loaded) {
+            (Some(url), Some(keys)) => Some((url.as_str(), keys.as_slice())),
+            _ => None,
+        };
+        inventory::duplicate_check(inventory, local);
+    }
+    pub fn keystore_req(&self, dir: &str) {
+        let banner = Rezzy{ message: format!("\nValidator Keystores: {}", dir) };
+        banner.bold();
+
+        // ownership is compared against the user the validator client runs as
+        let uid = self.vc.as_ref()
+            .and_then(|c| vc::vc_processes(c.as_str()).first().map(|(pid, _)| *pid))
+            .and_then(|pid| fs::metadata(format!("/proc/{}", pid)).ok())
+            .map(|m| m.uid());
+        keystore::keystore_check(dir, uid);
+    }
+    pub fn vc_req(&self) -> Option<Vec<String>> {
+        let client = self.vc.as_ref()?;
+        let banner = Rezzy{ message: format!("\nValidator Client: {}", client) };
+        banner.bold();
+
+        let loaded = vc::vc_check(client.as_str(), self.keymanager_url.as_ref(), self.keymanager_token.as_ref(), self.settings.fee_recipient.as_ref())?;
+        if !self.validators.is_empty() {
+            let node = Endpoint::new(format!("http://{}", self.eth2_http_addr).as_str());
+            match validator::pubkeys(&node, &self.validators) {
+                Ok(expected) => vc::keyset_check(client.as_str(), &loaded, &expected),
+                Err(e) => {
+                    let msg = Rezzy{ message: format!("Unable to look up validator pubkeys: {}", e) };
+                    msg.write_red();
+                },
             }
         }
-        match self.eth2 {
-            Eth2Client::LIGHTHOUSE | Eth2Client::NIMBUS | Eth2Client::TEKU => {
-                match TcpListener::bind(&self.eth2_listener_addr) {
-                    Ok(_) => {
-                        let msg = Rezzy{ message: format!("{:?} IS NOT LISTENING ON PORT: 9000", self.eth2) };
-                        msg.write_red();
-                    },
-                    Err(e) => {
-                        if e.kind() == ErrorKind::AddrInUse {
-                            let msg = Rezzy{ message: format!("{:?} is listening on port: 9000", self.eth2) };
-                            msg.write_green();
-                        } else {
-                            let msg = Rezzy{ message: format!("{:?} misc error when listening on 9000", e) };
-                            msg.write_yellow();
-                        }
-                    }
-                }   
-            }
-            Eth2Client::PRYSM => {
-                match TcpListener::bind(&self.eth2_listener_addr) {
-                    Ok(_) => {
-                        let msg = Rezzy{ message: format!("{:?} IS NOT LISTENING ON PORT: 4000", self.eth2) };
-                        msg.write_red();
-                    },
-                    Err(e) => {
-                        if e.kind() == ErrorKind::AddrInUse {
-                            let msg = Rezzy{ message: format!("{:?} is listening on port: 4000", self.eth2) };
-                            msg.write_green();
-                        } else {
-                            let msg = Rezzy{ message: format!("{:?} misc error when listening on 4000", e) };
-                            msg.write_yellow();
-                        }
-                    }
-                }   
-            } 
-            _ => {
-                // figure out a better way of handling this,
-            }
+        Some(loaded)
+    }
+    pub fn validator_req(&self, validators: &[String]) {
+        let banner = Rezzy{ message: format!("\nValidators: {}", validators.len()) };
+        banner.bold();
+
+        validator::validator_status_check(
+            format!("{:?}", self.eth2).as_str(),
+            format!("http://{}", self.eth2_http_addr).as_str(),
+            validators,
+        );
+        validator::validator_performance_check(
+            format!("{:?}", self.eth2).as_str(),
+            format!("http://{}", self.eth2_http_addr).as_str(),
+            validators,
+            &self.network,
+            self.validator_epochs,
+        );
+        validator::validator_duty_check(
+            format!("{:?}", self.eth2).as_str(),
+            format!("http://{}", self.eth2_http_addr).as_str(),
+            validators,
+            &self.network,
+            self.duty_warn_epochs,
+        );
+        validator::withdrawal_check(
+            format!("{:?}", self.eth2).as_str(),
+            format!("http://{}", self.eth2_http_addr).as_str(),
+            validators,
+            &self.settings.withdrawal_addresses,
+        );
+    }
+    pub fn link_req(&self) {
+        let banner = Rezzy{ message: format!("\nETH1/ETH2 Connection: {}/{:?}", self.eth1.name, self.eth2) };
+        banner.bold();
+
+        execution_link_check(
+            format!("{:?}", self.eth2).as_str(),
+            format!("http://{}", self.eth2_http_addr).as_str(),
+            self.eth1.name.as_str(),
+            &self.eth1_http_addr,
+        );
+    }
+    pub fn engine_req(&self) {
+        let banner = Rezzy{ message: String::from("\nEngine API:") };
+        banner.bold();
+
+        engine::engine_check(
+            self.eth1.name.as_str(),
+            format!("{:?}", self.eth2).as_str(),
+            &self.eth1_engine_addr,
+            self.jwt_secret.as_ref(),
+            self.eth1_service.as_ref(),
+            self.eth2_service.as_ref(),
+        );
+    }
+    pub fn net_req(&self) {
+        let banner = Rezzy{ message: String::from("\nNetwork Requirements:") };
+        banner.bold();
+        listen_check(self.eth1.name.as_str(), &self.eth1_listener_addr, "");
+        listen_check(self.eth1.name.as_str(), &self.eth1_http_addr, " for JSON RPC");
+        if self.eth2 != Eth2Client::NONE {
+            listen_check(format!("{:?}", self.eth2).as_str(), &self.eth2_listener_addr, "");
         }
         match TcpListener::bind("127.0.0.1:22") {
             Ok(_) => {
-                let msg = Rezzy{ message: format!("No default ssh agent running on port: 22") };
+                let msg = Rezzy{ message: String::from("No default ssh agent running on port: 22") };
                 msg.write_green();
             },
             Err(e) => {
@@ -277,7 +676,7 @@ impl Valid8r {
                     let msg = Rezzy{ message: format!("{:?} security best practices recommend moving the standard ssh port", self.eth1) };
                     msg.write_red();
                 } else if e.kind()  == ErrorKind::PermissionDenied {
-                    let msg = Rezzy{ message: format!("Could not access default ssh port 22(run as root)") };
+                    let msg = Rezzy{ message: String::from("Could not access default ssh port 22(run as root)") };
                     msg.write_yellow();
                 } else {
                     let msg = Rezzy{ message: format!("{:?} misc error when listening on 22", e) };
@@ -287,7 +686,7 @@ impl Valid8r {
         }
     }
     pub fn sys_req(&self) {
-        let banner = Rezzy{ message: format!("\nSystem Requirements:") };
+        let banner = Rezzy{ message: String::from("\nSystem Requirements:") };
         banner.bold();
         match ntp::request(&self.ntp_endpoint) {
             Ok(val) => {
@@ -296,7 +695,7 @@ impl Valid8r {
                 println!("Time Sync - NTP {} vs LOCAL {:?}", ntp_time, loc.time());
             },
             Err(_) => {
-                let msg = Rezzy{ message: format!("Could not get NTP time") };
+                let msg = Rezzy{ message: String::from("Could not get NTP time") };
                 msg.write_red();
             },
         };
@@ -322,7 +721,7 @@ impl Valid8r {
                     }
                 },
                 None => {
-                    let msg = Rezzy{ message: format!("Could not get OS Version") };
+                    let msg = Rezzy{ message: String::from("Could not get OS Version") };
                     msg.write_red();
                 },
             };
@@ -339,7 +738,7 @@ impl Valid8r {
                     }
                 },
                 None => {
-                    let msg = Rezzy{ message: format!("Could not get OS Version") };
+                    let msg = Rezzy{ message: String::from("Could not get OS Version") };
                     msg.write_red(); 
                 }
             };
@@ -391,6 +790,24 @@ impl Valid8r {
     }    
 }
 
+fn listen_check(name: &str, addr: &str, desc: &str) {
+    match TcpListener::bind(addr) {
+        Ok(_) => {
+            let msg = Rezzy{ message: format!("{} IS NOT LISTENING{} ON PORT: {}", name, desc, addr) };
+            msg.write_red();
+        },
+        Err(e) => {
+            if e.kind() == ErrorKind::AddrInUse {
+                let msg = Rezzy{ message: format!("{} is listening{} on port: {}", name, desc, addr) };
+                msg.write_green();
+            } else {
+                let msg = Rezzy{ message: format!("{:?} misc error when listening on {}", e, addr) };
+                msg.write_yellow();
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,18 +815,44 @@ mod tests {
     #[test]
     fn upper_arg_match() {
         let cfg = Config{ 
-            eth1: String::from("GETH"),
-            eth2: String::from("LIGHTHOUSE"),
+            eth1: Some(String::from("GETH")),
+            eth2: Some(String::from("LIGHTHOUSE")),
             eth1_listener_port: Some(30303),
             eth1_http_port: Some(8545),
+            eth1_engine_port: None,
+            jwt_secret: None,
             eth2_listener_port: Some(9000),
             eth2_http_port: Some(5052),
-            testnet: Some(String::from("Ropsten")),
+            network: NetworkName::SEPOLIA,
+            testnet: None,
+            network_file: None,
             ntp_endpoint: Some(String::from("0.0.0.0")),
-            infura_endpoint: Some(String::from("0.0.0.0")),
+            config: None,
+            eth1_reference: vec![String::from("http://0.0.0.0:8545")],
+            infura_endpoint: None,
+            eth2_reference: Vec::new(),
+            reference_quorum: None,
+            no_reference: false,
+            block_lag_warn: 2,
+            block_lag_fail: 10,
+            finality_lag: 4,
+            validator: vec![],
+            validator_file: None,
+            validator_epochs: 3,
+            duty_warn_epochs: 2,
+            vc: None,
+            keymanager_url: None,
+            keymanager_token: None,
+            keystores: None,
+            inventory: None,
+            compose_file: None,
+            cmd: None,
         };
         let val = Valid8r::new(cfg);
-        assert_eq!(val.eth1, String::from("GETH"));
+        assert_eq!(val.eth1.name, String::from("GETH"));
         assert_eq!(val.eth2, Eth2Client::LIGHTHOUSE);
+        assert_eq!(val.eth1.network.chain_id, 11155111);
+        assert_eq!(val.eth1.references.endpoints, vec![Endpoint::new("http://0.0.0.0:8545")]);
+        assert_eq!(val.eth2_references.endpoints, val.network.eth2_references);
     }
 }
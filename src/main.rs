@@ -4,6 +4,10 @@ use structopt::StructOpt;
 // cli command: valid8r src/main.rs --pattern main
 fn main() {
     let cfg = Config::from_args();
+    if let Some(cmd) = &cfg.cmd {
+        valid8r::command(&cfg, cmd);
+        return;
+    }
     let valid = Valid8r::new(cfg);
 
     if let Err(e) = valid.run() {
@@ -0,0 +1,274 @@
+use std::fs;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use serde::Deserialize;
+use anyhow::{anyhow, Result};
+use crate::reference::Endpoint;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NetworkName {
+    MAINNET,
+    SEPOLIA,
+    HOLESKY,
+    HOODI,
+    CUSTOM,
+}
+
+impl NetworkName {
+    pub fn variants() -> [&'static str; 5] {
+        ["mainnet", "sepolia", "holesky", "hoodi", "custom"]
+    }
+}
+
+impl FromStr for NetworkName {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<NetworkName> {
+        match s.to_lowercase().as_str() {
+            "mainnet" => Ok(NetworkName::MAINNET),
+            "sepolia" => Ok(NetworkName::SEPOLIA),
+            "holesky" => Ok(NetworkName::HOLESKY),
+            "hoodi" => Ok(NetworkName::HOODI),
+            "custom" => Ok(NetworkName::CUSTOM),
+            _ => Err(anyhow!("unknown network {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Fork {
+    pub name: String,
+    pub epoch: u64,
+    pub version: String,
+}
+
+// everything valid8r needs to know about a network, custom devnets are read from a yaml/json file of this shape
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Network {
+    pub name: String,
+    pub chain_id: u64,
+    pub genesis_hash: String,
+    pub genesis_validators_root: String,
+    pub genesis_time: u64,
+    pub genesis_fork_version: String,
+    pub deposit_contract: String,
+    #[serde(default = "default_seconds_per_slot")]
+    pub seconds_per_slot: u64,
+    #[serde(default = "default_slots_per_epoch")]
+    pub slots_per_epoch: u64,
+    #[serde(default = "default_epochs_per_sync_committee_period")]
+    pub epochs_per_sync_committee_period: u64,
+    #[serde(default = "default_eth1_p2p_port")]
+    pub eth1_p2p_port: u16,
+    #[serde(default = "default_eth2_p2p_port")]
+    pub eth2_p2p_port: u16,
+    #[serde(default)]
+    pub forks: Vec<Fork>,
+    #[serde(default)]
+    pub eth1_references: Vec<Endpoint>,
+    #[serde(default)]
+    pub eth2_references: Vec<Endpoint>,
+}
+
+fn default_seconds_per_slot() -> u64 { 12 }
+fn default_slots_per_epoch() -> u64 { 32 }
+fn default_epochs_per_sync_committee_period() -> u64 { 256 }
+fn default_eth1_p2p_port() -> u16 { 30303 }
+fn default_eth2_p2p_port() -> u16 { 9000 }
+
+fn fork(name: &str, epoch: u64, version: &str) -> Fork {
+    Fork{ name: String::from(name), epoch, version: String::from(version) }
+}
+
+impl Network {
+    pub fn new(name: NetworkName) -> Option<Network> {
+        match name {
+            NetworkName::MAINNET => Some(Network::mainnet()),
+            NetworkName::SEPOLIA => Some(Network::sepolia()),
+            NetworkName::HOLESKY => Some(Network::holesky()),
+            NetworkName::HOODI => Some(Network::hoodi()),
+            NetworkName::CUSTOM => None,
+        }
+    }
+    pub fn from_file(path: &str) -> Result<Network> {
+        let contents = fs::read_to_string(path)?;
+        Network::parse(contents.as_str())
+    }
+    // slot and epoch lengths are divided by everywhere, so zero is rejected up front
+    pub fn parse(contents: &str) -> Result<Network> {
+        let net: Network = serde_yaml::from_str(contents)?;
+        if net.seconds_per_slot == 0 {
+            return Err(anyhow!("seconds_per_slot must be greater than 0"));
+        }
+        if net.slots_per_epoch == 0 {
+            return Err(anyhow!("slots_per_epoch must be greater than 0"));
+        }
+        if net.epochs_per_sync_committee_period == 0 {
+            return Err(anyhow!("epochs_per_sync_committee_period must be greater than 0"));
+        }
+        Ok(net)
+    }
+    // the /eth/v1/config/spec values a beacon node on this network must report
+    pub fn spec(&self) -> BTreeMap<String, String> {
+        let mut spec = BTreeMap::new();
+        spec.insert(String::from("SECONDS_PER_SLOT"), self.seconds_per_slot.to_string());
+        spec.insert(String::from("SLOTS_PER_EPOCH"), self.slots_per_epoch.to_string());
+        spec.insert(String::from("EPOCHS_PER_SYNC_COMMITTEE_PERIOD"), self.epochs_per_sync_committee_period.to_string());
+        spec.insert(String::from("GENESIS_FORK_VERSION"), self.genesis_fork_version.to_lowercase());
+        spec.insert(String::from("DEPOSIT_CHAIN_ID"), self.chain_id.to_string());
+        spec.insert(String::from("DEPOSIT_CONTRACT_ADDRESS"), self.deposit_contract.to_lowercase());
+        for f in &self.forks {
+            let name = f.name.to_uppercase();
+            spec.insert(format!("{}_FORK_EPOCH", name), f.epoch.to_string());
+            spec.insert(format!("{}_FORK_VERSION", name), f.version.to_lowercase());
+        }
+        spec
+    }
+    pub fn mainnet() -> Network {
+        Network{
+            name: String::from("mainnet"),
+            chain_id: 1,
+            genesis_hash: String::from("0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"),
+            genesis_validators_root: String::from("0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"),
+            genesis_time: 1606824023,
+            genesis_fork_version: String::from("0x00000000"),
+            deposit_contract: String::from("0x00000000219ab540356cbb839cbe05303d7705fa"),
+            seconds_per_slot: 12,
+            slots_per_epoch: 32,
+            epochs_per_sync_committee_period: 256,
+            eth1_p2p_port: 30303,
+            eth2_p2p_port: 9000,
+            forks: vec![
+                fork("altair", 74240, "0x01000000"),
+                fork("bellatrix", 144896, "0x02000000"),
+                fork("capella", 194048, "0x03000000"),
+                fork("deneb", 269568, "0x04000000"),
+                fork("electra", 364032, "0x05000000"),
+                fork("fulu", 411392, "0x06000000"),
+            ],
+            eth1_references: vec![Endpoint::new("https://ethereum-rpc.publicnode.com")],
+            eth2_references: vec![Endpoint::new("https://ethereum-beacon-api.publicnode.com")],
+        }
+    }
+    pub fn sepolia() -> Network {
+        Network{
+            name: String::from("sepolia"),
+            chain_id: 11155111,
+            genesis_hash: String::from("0x25a5cc106eea7138acab33231d7160d69cb777ee0c2c553fcddf5138993e6dd9"),
+            genesis_validators_root: String::from("0xd8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078"),
+            genesis_time: 1655733600,
+            genesis_fork_version: String::from("0x90000069"),
+            deposit_contract: String::from("0x7f02c3e3c98b133055b8b348b2ac625669ed295d"),
+            seconds_per_slot: 12,
+            slots_per_epoch: 32,
+            epochs_per_sync_committee_period: 256,
+            eth1_p2p_port: 30303,
+            eth2_p2p_port: 9000,
+            forks: vec![
+                fork("altair", 50, "0x90000070"),
+                fork("bellatrix", 100, "0x90000071"),
+                fork("capella", 56832, "0x90000072"),
+                fork("deneb", 132608, "0x90000073"),
+                fork("electra", 222464, "0x90000074"),
+                fork("fulu", 272640, "0x90000075"),
+            ],
+            eth1_references: vec![Endpoint::new("https://ethereum-sepolia-rpc.publicnode.com")],
+            eth2_references: vec![Endpoint::new("https://ethereum-sepolia-beacon-api.publicnode.com")],
+        }
+    }
+    pub fn holesky() -> Network {
+        Network{
+            name: String::from("holesky"),
+            chain_id: 17000,
+            genesis_hash: String::from("0xb5f7f912443c940f21fd611f12828d75b534364ed9e95ca4e307729a4661bde4"),
+            genesis_validators_root: String::from("0x9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"),
+            genesis_time: 1695902400,
+            genesis_fork_version: String::from("0x01017000"),
+            deposit_contract: String::from("0x4242424242424242424242424242424242424242"),
+            seconds_per_slot: 12,
+            slots_per_epoch: 32,
+            epochs_per_sync_committee_period: 256,
+            eth1_p2p_port: 30303,
+            eth2_p2p_port: 9000,
+            forks: vec![
+                fork("altair", 0, "0x02017000"),
+                fork("bellatrix", 0, "0x03017000"),
+                fork("capella", 256, "0x04017000"),
+                fork("deneb", 29696, "0x05017000"),
+                fork("electra", 115968, "0x06017000"),
+                fork("fulu", 165120, "0x07017000"),
+            ],
+            eth1_references: vec![Endpoint::new("https://ethereum-holesky-rpc.publicnode.com")],
+            eth2_references: vec![Endpoint::new("https://ethereum-holesky-beacon-api.publicnode.com")],
+        }
+    }
+    pub fn hoodi() -> Network {
+        Network{
+            name: String::from("hoodi"),
+            chain_id: 560048,
+            genesis_hash: String::from("0xbbe312868b376a3001692a646dd2d7d1e4406380dfd86b98aa8a34d1557c971b"),
+            genesis_validators_root: String::from("0x212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f"),
+            genesis_time: 1742213400,
+            genesis_fork_version: String::from("0x10000910"),
+            deposit_contract: String::from("0x00000000219ab540356cbb839cbe05303d7705fa"),
+            seconds_per_slot: 12,
+            slots_per_epoch: 32,
+            epochs_per_sync_committee_period: 256,
+            eth1_p2p_port: 30303,
+            eth2_p2p_port: 9000,
+            forks: vec![
+                fork("altair", 0, "0x20000910"),
+                fork("bellatrix", 0, "0x30000910"),
+                fork("capella", 0, "0x40000910"),
+                fork("deneb", 0, "0x50000910"),
+                fork("electra", 2048, "0x60000910"),
+                fork("fulu", 50688, "0x70000910"),
+            ],
+            eth1_references: vec![Endpoint::new("https://ethereum-hoodi-rpc.publicnode.com")],
+            eth2_references: vec![Endpoint::new("https://ethereum-hoodi-beacon-api.publicnode.com")],
+        }
+    }
+}
+
+// name of a well known chain id, used to say which network a misconfigured node is actually on
+pub fn chain_name(chain_id: u64) -> &'static str {
+    match chain_id {
+        1 => "mainnet",
+        5 => "goerli",
+        17000 => "holesky",
+        560048 => "hoodi",
+        11155111 => "sepolia",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_network_defaults() {
+        let devnet = r#"
+name: devnet-7
+chain_id: 7011893
+genesis_hash: "0x01"
+genesis_validators_root: "0x02"
+genesis_time: 1700000000
+genesis_fork_version: "0x10000038"
+deposit_contract: "0x4242424242424242424242424242424242424242"
+eth2_p2p_port: 9100
+forks:
+  - { name: electra, epoch: 0, version: "0x60000038" }
+"#;
+        let net = Network::parse(devnet).unwrap();
+        assert_eq!(net.seconds_per_slot, 12);
+        assert_eq!(net.epochs_per_sync_committee_period, 256);
+        assert_eq!(net.eth1_p2p_port, 30303);
+        assert_eq!(net.eth2_p2p_port, 9100);
+        assert_eq!(net.forks[0].name, "electra");
+        assert_eq!("Hoodi".parse::<NetworkName>().unwrap(), NetworkName::HOODI);
+        assert!(Network::parse(format!("{}seconds_per_slot: 0\n", devnet).as_str()).is_err());
+        assert!(Network::parse(format!("{}slots_per_epoch: 0\n", devnet).as_str()).is_err());
+    }
+}
@@ -30,3 +30,17 @@ impl Rezzy {
         println!("{}", self.message.bold()); 
     }
 }
+
+// human readable duration for sync ETAs, e.g. 1d 4h 12m
+pub fn fmt_duration(secs: u64) -> String {
+    let (d, h, m, s) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60, secs % 60);
+    if d > 0 {
+        format!("{}d {}h {}m", d, h, m)
+    } else if h > 0 {
+        format!("{}h {}m", h, m)
+    } else if m > 0 {
+        format!("{}m {}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
@@ -0,0 +1,168 @@
+extern crate reqwest;
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use anyhow::{anyhow, Result};
+use crate::output::Rezzy;
+use crate::eth1::{RpcRequest, RpcResponse};
+
+// a reference execution or beacon node, headers carry provider auth(api keys, bearer tokens)
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Endpoint {
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Endpoint {
+    pub fn new(url: &str) -> Endpoint {
+        Endpoint{ url: String::from(url), headers: BTreeMap::new() }
+    }
+    fn send(&self, req: reqwest::blocking::RequestBuilder) -> Result<reqwest::blocking::Response> {
+        let mut req = req.header("Content-Type", "application/json");
+        for (k, v) in &self.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+        Ok(req.send()?)
+    }
+    // result field of a successful json rpc call, errors on http or rpc failure
+    pub fn rpc(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let req = RpcRequest {
+            jsonrpc: String::from("2.0"),
+            method: String::from(method),
+            params,
+            id: String::from("1"),
+        };
+        let client = reqwest::blocking::Client::new();
+        let res = self.send(client.post(self.url.as_str()).body(serde_json::to_string(&req)?))?;
+        if res.status() != reqwest::StatusCode::OK {
+            return Err(anyhow!("{} returned http status {}", method, res.status()));
+        }
+        let j: RpcResponse = res.json()?;
+        if let Some(e) = j.error {
+            return Err(anyhow!("{} returned error: {}", method, e));
+        }
+        match j.result {
+            Some(r) => Ok(r),
+            None => Err(anyhow!("{} returned no result", method)),
+        }
+    }
+    // json body of a successful beacon api GET
+    pub fn get(&self, path: &str) -> Result<serde_json::Value> {
+        let url = format!("{}{}", self.url.trim_end_matches('/'), path);
+        let client = reqwest::blocking::Client::new();
+        let res = self.send(client.get(url.as_str()))?;
+        if !res.status().is_success() {
+            return Err(anyhow!("{} returned http status {}", path, res.status()));
+        }
+        res.json().map_err(|e| anyhow!("{} returned a body that isn't json: {}", path, e))
+    }
+    // json body of a GET, None when the api answers 404(e.g. a keymanager setting that isn't set)
+    pub fn get_optional(&self, path: &str) -> Result<Option<serde_json::Value>> {
+        let url = format!("{}{}", self.url.trim_end_matches('/'), path);
+        let client = reqwest::blocking::Client::new();
+        let res = self.send(client.get(url.as_str()))?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(anyhow!("{} returned http status {}", path, res.status()));
+        }
+        res.json().map(Some).map_err(|e| anyhow!("{} returned a body that isn't json: {}", path, e))
+    }
+    // json body of a successful beacon api POST, e.g. the rewards endpoints
+    pub fn post(&self, path: &str, body: serde_json::Value) -> Result<serde_json::Value> {
+        let url = format!("{}{}", self.url.trim_end_matches('/'), path);
+        let client = reqwest::blocking::Client::new();
+        let res = self.send(client.post(url.as_str()).body(body.to_string()))?;
+        if !res.status().is_success() {
+            return Err(anyhow!("{} returned http status {}", path, res.status()));
+        }
+        res.json().map_err(|e| anyhow!("{} returned a body that isn't json: {}", path, e))
+    }
+    // http status of a GET, for endpoints like /eth/v1/node/health that answer with no body
+    pub fn status(&self, path: &str) -> Result<reqwest::StatusCode> {
+        let url = format!("{}{}", self.url.trim_end_matches('/'), path);
+        let client = reqwest::blocking::Client::new();
+        Ok(self.send(client.get(url.as_str()))?.status())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct References {
+    pub endpoints: Vec<Endpoint>,
+    pub quorum: usize,
+    pub lag_warn: u64,
+    pub lag_fail: u64,
+}
+
+impl References {
+    pub fn new(endpoints: Vec<Endpoint>) -> References {
+        References{ endpoints, quorum: 1, lag_warn: 2, lag_fail: 10 }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+    // highest head at least `quorum` references have reached, so one runaway or lagging reference can't skew it
+    pub fn quorum_head(&self, heads: &[u64]) -> Option<u64> {
+        let mut heads = heads.to_vec();
+        heads.sort_unstable_by(|a, b| b.cmp(a));
+        heads.get(self.quorum.max(1) - 1).copied()
+    }
+    pub fn lag_check(&self, name: &str, unit: &str, local: u64, heads: &[u64]) {
+        let head = match self.quorum_head(heads) {
+            Some(h) => h,
+            None => {
+                let msg = Rezzy{ message: format!("Only {} of {} reference endpoints answered, a quorum of {} is required to compare {} head", heads.len(), self.endpoints.len(), self.quorum, name) };
+                msg.write_yellow();
+                return;
+            },
+        };
+
+        let lag = head.saturating_sub(local);
+        if lag <= self.lag_warn {
+            let msg = Rezzy{ message: format!("{} {} {} is within {} of the reference head {}({} of {} references)", name, unit, local, self.lag_warn, head, heads.len(), self.endpoints.len()) };
+            msg.write_green();
+        } else if lag <= self.lag_fail {
+            let msg = Rezzy{ message: format!("{} {} {} is {} behind the reference head {}", name, unit, local, lag, head) };
+            msg.write_yellow();
+        } else {
+            let msg = Rezzy{ message: format!("{} {} {} is {} BEHIND the reference head {}", name, unit, local, lag, head) };
+            msg.write_red();
+        }
+    }
+    // any quorum of disagreeing references is a fork, even when others agree
+    pub fn fork_check(&self, name: &str, agree: usize, disagree: usize) {
+        let quorum = self.quorum.max(1);
+        if agree + disagree == 0 {
+            // nothing comparable came back, the reasons have already been printed
+            return;
+        }
+        if disagree >= quorum {
+            let msg = Rezzy{ message: format!("{} is on a FORK: {} of {} references disagree", name, disagree, self.endpoints.len()) };
+            msg.write_red();
+        } else if disagree > 0 {
+            let msg = Rezzy{ message: format!("{} fork check: {} references agree but {} DISAGREE, quorum is {}", name, agree, disagree, quorum) };
+            msg.write_yellow();
+        } else if agree >= quorum {
+            let msg = Rezzy{ message: format!("{} is on the same chain as {} of {} references", name, agree, self.endpoints.len()) };
+            msg.write_green();
+        } else {
+            let msg = Rezzy{ message: format!("{} fork check inconclusive: {} references agree, quorum is {}", name, agree, quorum) };
+            msg.write_yellow();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quorum_head_ignores_outliers() {
+        let mut refs = References::new(vec![Endpoint::new("a"), Endpoint::new("b"), Endpoint::new("c")]);
+        assert_eq!(refs.quorum_head(&[100, 5000, 98]), Some(5000));
+        refs.quorum = 2;
+        assert_eq!(refs.quorum_head(&[100, 5000, 98]), Some(100));
+        assert_eq!(refs.quorum_head(&[100]), None);
+    }
+}
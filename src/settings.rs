@@ -0,0 +1,35 @@
+use std::fs;
+use serde::Deserialize;
+use anyhow::Result;
+use crate::reference::Endpoint;
+
+// valid8r config file(yaml/json), for anything too structured to pass as flags
+#[derive(Debug, PartialEq, Clone, Default, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub references: ReferenceSettings,
+    // pubkeys or indices of the validators to check
+    #[serde(default)]
+    pub validators: Vec<String>,
+    // execution addresses validator withdrawals are expected to go to
+    #[serde(default)]
+    pub withdrawal_addresses: Vec<String>,
+    // execution address every validator's fee recipient should be
+    pub fee_recipient: Option<String>,
+}
+
+// an empty list disables reference comparisons, leaving a list out falls back to the network defaults
+#[derive(Debug, PartialEq, Clone, Default, Deserialize)]
+pub struct ReferenceSettings {
+    pub execution: Option<Vec<Endpoint>>,
+    pub beacon: Option<Vec<Endpoint>>,
+    pub quorum: Option<usize>,
+}
+
+impl Settings {
+    pub fn from_file(path: &str) -> Result<Settings> {
+        let contents = fs::read_to_string(path)?;
+        let settings: Settings = serde_yaml::from_str(contents.as_str())?;
+        Ok(settings)
+    }
+}
@@ -0,0 +1,287 @@
+use std::fs;
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use anyhow::Result;
+use crate::output::Rezzy;
+use crate::network::Network;
+use crate::validator::short_key;
+
+// EIP-3076 slashing protection interchange, the format every client imports and exports
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Interchange {
+    pub metadata: Metadata,
+    pub data: Vec<Record>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Metadata {
+    pub interchange_format_version: String,
+    pub genesis_validators_root: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Record {
+    pub pubkey: String,
+    #[serde(default)]
+    pub signed_blocks: Vec<SignedBlock>,
+    #[serde(default)]
+    pub signed_attestations: Vec<SignedAttestation>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct SignedBlock {
+    pub slot: String,
+    pub signing_root: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct SignedAttestation {
+    pub source_epoch: String,
+    pub target_epoch: String,
+    pub signing_root: Option<String>,
+}
+
+fn is_hex(s: &str, bytes: usize) -> bool {
+    s.len() == 2 + bytes * 2 && s.starts_with("0x") && s[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// what a record signed, with the numbers parsed, for slashing and range checks
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct History {
+    pub blocks: Vec<(u64, Option<String>)>,
+    pub attestations: Vec<(u64, u64, Option<String>)>,
+}
+
+impl Interchange {
+    pub fn from_file(path: &str) -> Result<Interchange> {
+        let contents = fs::read_to_string(path)?;
+        let i: Interchange = serde_json::from_str(contents.as_str())?;
+        Ok(i)
+    }
+    // schema violations serde can't see: version, hex lengths and decimal strings
+    pub fn schema_errors(&self) -> Vec<String> {
+        let mut errs = Vec::new();
+        if self.metadata.interchange_format_version != "5" {
+            errs.push(format!("metadata.interchange_format_version is {}, only 5 is defined", self.metadata.interchange_format_version));
+        }
+        if !is_hex(self.metadata.genesis_validators_root.as_str(), 32) {
+            errs.push(String::from("metadata.genesis_validators_root is not a 32 byte hex string"));
+        }
+        let root_ok = |r: &Option<String>| r.as_ref().is_none_or(|r| is_hex(r.as_str(), 32));
+        let num_ok = |n: &str| !n.is_empty() && n.parse::<u64>().is_ok();
+        for (i, r) in self.data.iter().enumerate() {
+            if !is_hex(r.pubkey.as_str(), 48) {
+                errs.push(format!("data[{}].pubkey is not a 48 byte hex string", i));
+            }
+            for (j, b) in r.signed_blocks.iter().enumerate() {
+                if !num_ok(b.slot.as_str()) {
+                    errs.push(format!("data[{}].signed_blocks[{}].slot {:?} is not a decimal string", i, j, b.slot));
+                }
+                if !root_ok(&b.signing_root) {
+                    errs.push(format!("data[{}].signed_blocks[{}].signing_root is not a 32 byte hex string", i, j));
+                }
+            }
+            for (j, a) in r.signed_attestations.iter().enumerate() {
+                if !num_ok(a.source_epoch.as_str()) || !num_ok(a.target_epoch.as_str()) {
+                    errs.push(format!("data[{}].signed_attestations[{}] epochs are not decimal strings", i, j));
+                } else if a.source_epoch.parse::<u64>().ok() > a.target_epoch.parse::<u64>().ok() {
+                    errs.push(format!("data[{}].signed_attestations[{}] source epoch {} is after target epoch {}", i, j, a.source_epoch, a.target_epoch));
+                }
+                if !root_ok(&a.signing_root) {
+                    errs.push(format!("data[{}].signed_attestations[{}].signing_root is not a 32 byte hex string", i, j));
+                }
+            }
+        }
+        errs
+    }
+    // signing history per pubkey, records repeated for one pubkey are merged as the EIP asks importers to
+    pub fn histories(&self) -> BTreeMap<String, History> {
+        let mut out: BTreeMap<String, History> = BTreeMap::new();
+        for r in &self.data {
+            let h = out.entry(r.pubkey.to_lowercase()).or_default();
+            h.blocks.extend(r.signed_blocks.iter()
+                .filter_map(|b| Some((b.slot.parse().ok()?, b.signing_root.clone()))));
+            h.attestations.extend(r.signed_attestations.iter()
+                .filter_map(|a| Some((a.source_epoch.parse().ok()?, a.target_epoch.parse().ok()?, a.signing_root.clone()))));
+        }
+        out
+    }
+}
+
+// two messages for the same slot/target conflict unless they carry the same signing root, or both carry none
+fn conflicting(a: &Option<String>, b: &Option<String>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.to_lowercase() != b.to_lowercase(),
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+impl History {
+    // double proposals, double votes and surround votes this history already contains
+    pub fn slashable(&self) -> Vec<String> {
+        let mut found = Vec::new();
+
+        let mut blocks = self.blocks.clone();
+        blocks.sort();
+        for w in blocks.windows(2) {
+            if w[0].0 == w[1].0 && conflicting(&w[0].1, &w[1].1) {
+                found.push(format!("double proposal at slot {}", w[0].0));
+            }
+        }
+
+        let mut by_target = self.attestations.clone();
+        by_target.sort_by_key(|a| a.1);
+        for w in by_target.windows(2) {
+            if w[0].1 == w[1].1 && (w[0].0 != w[1].0 || conflicting(&w[0].2, &w[1].2)) {
+                found.push(format!("double vote for target epoch {}", w[0].1));
+            }
+        }
+
+        // walking in source order, any earlier vote with a lower source and higher target surrounds this one
+        let mut by_source = self.attestations.clone();
+        by_source.sort_by_key(|a| (a.0, a.1));
+        let mut widest: Option<(u64, u64)> = None;
+        let mut i = 0;
+        while i < by_source.len() {
+            let source = by_source[i].0;
+            let group: Vec<(u64, u64)> = by_source[i..].iter().take_while(|a| a.0 == source).map(|a| (a.0, a.1)).collect();
+            for (s, t) in &group {
+                if let Some((ws, wt)) = widest {
+                    if wt > *t {
+                        found.push(format!("surround vote: {}->{} surrounds {}->{}", ws, wt, s, t));
+                    }
+                }
+            }
+            if let Some(&(s, t)) = group.iter().max_by_key(|a| a.1) {
+                if widest.is_none_or(|(_, wt)| t > wt) {
+                    widest = Some((s, t));
+                }
+            }
+            i += group.len();
+        }
+        // three or more entries for one slot or target are one finding
+        found.dedup();
+        found
+    }
+    pub fn slot_range(&self) -> Option<(u64, u64)> {
+        let slots = self.blocks.iter().map(|b| b.0);
+        Some((slots.clone().min()?, slots.max()?))
+    }
+    pub fn epoch_ranges(&self) -> Option<((u64, u64), (u64, u64))> {
+        let sources = self.attestations.iter().map(|a| a.0);
+        let targets = self.attestations.iter().map(|a| a.1);
+        Some(((sources.clone().min()?, sources.max()?), (targets.clone().min()?, targets.max()?)))
+    }
+}
+
+// `valid8r slashing-db`: is this interchange file safe to import on `network`, false when it isn't
+pub fn slashing_db_check(path: &str, network: &Network) -> bool {
+    let banner = Rezzy{ message: format!("Slashing Protection Interchange: {}", path) };
+    banner.bold();
+
+    let interchange = match Interchange::from_file(path) {
+        Ok(i) => i,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("{} is NOT a valid EIP-3076 interchange file: {}", path, e) };
+            msg.write_red();
+            return false;
+        },
+    };
+
+    let errs = interchange.schema_errors();
+    if errs.is_empty() {
+        let msg = Rezzy{ message: format!("Interchange format version {} with {} records is well formed", interchange.metadata.interchange_format_version, interchange.data.len()) };
+        msg.write_green();
+    }
+    for e in &errs {
+        let msg = Rezzy{ message: format!("Schema: {}", e) };
+        msg.write_red();
+    }
+
+    let gvr = interchange.metadata.genesis_validators_root.to_lowercase();
+    let same_network = gvr == network.genesis_validators_root.to_lowercase();
+    if same_network {
+        let msg = Rezzy{ message: format!("Genesis validators root matches {}", network.name) };
+        msg.write_green();
+    } else {
+        let msg = Rezzy{ message: format!("Genesis validators root {} does NOT match {}({}), this file is from another network", gvr, network.name, network.genesis_validators_root) };
+        msg.write_red();
+    }
+
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for r in &interchange.data {
+        *counts.entry(r.pubkey.to_lowercase()).or_default() += 1;
+    }
+    for (key, n) in counts.iter().filter(|(_, n)| **n > 1) {
+        let msg = Rezzy{ message: format!("validator {} has {} records, clients merge them on import", short_key(key.as_str()), n) };
+        msg.write_yellow();
+    }
+
+    let histories = interchange.histories();
+    let mut slashable = 0;
+    for (key, h) in &histories {
+        let found = h.slashable();
+        for f in &found {
+            let msg = Rezzy{ message: format!("validator {} already signed a SLASHABLE message: {}", short_key(key.as_str()), f) };
+            msg.write_red();
+        }
+        if !found.is_empty() {
+            slashable += 1;
+        }
+
+        let blocks = match h.slot_range() {
+            Some((lo, hi)) => format!("{} blocks(slots {}-{})", h.blocks.len(), lo, hi),
+            None => String::from("no blocks"),
+        };
+        let attestations = match h.epoch_ranges() {
+            Some(((slo, shi), (tlo, thi))) => format!("{} attestations(source epochs {}-{}, target epochs {}-{})", h.attestations.len(), slo, shi, tlo, thi),
+            None => String::from("no attestations"),
+        };
+        let msg = Rezzy{ message: format!("validator {}: {}, {}", short_key(key.as_str()), blocks, attestations) };
+        if found.is_empty() {
+            msg.write_green();
+        } else {
+            msg.write_red();
+        }
+    }
+    if slashable == 0 {
+        let msg = Rezzy{ message: format!("No double proposals, double votes or surround votes across {} validators", histories.len()) };
+        msg.write_green();
+    }
+    errs.is_empty() && same_network && slashable == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slashable_history() {
+        let root = |b: u8| Some(format!("0x{}", hex::encode([b; 32])));
+        let clean = History{
+            blocks: vec![(10, root(1)), (12, root(2)), (12, root(2))],
+            attestations: vec![(1, 2, root(1)), (2, 3, root(2)), (3, 4, root(3))],
+        };
+        assert!(clean.slashable().is_empty());
+        assert_eq!(clean.slot_range(), Some((10, 12)));
+        assert_eq!(clean.epoch_ranges(), Some(((1, 3), (2, 4))));
+
+        // the same entry exported twice without signing roots
+        let rootless = History{
+            blocks: vec![(10, None), (10, None)],
+            attestations: vec![(1, 2, None), (1, 2, None)],
+        };
+        assert!(rootless.slashable().is_empty());
+
+        let bad = History{
+            blocks: vec![(10, root(1)), (10, root(2)), (10, root(3))],
+            attestations: vec![(1, 5, root(1)), (2, 3, root(2)), (2, 5, root(3)), (3, 5, root(4))],
+        };
+        let found = bad.slashable();
+        assert!(found.contains(&String::from("double proposal at slot 10")));
+        assert!(found.contains(&String::from("double vote for target epoch 5")));
+        assert!(found.contains(&String::from("surround vote: 1->5 surrounds 2->3")));
+        assert_eq!(found.len(), 3);
+    }
+}
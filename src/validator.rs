@@ -0,0 +1,558 @@
+use std::fs;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use crate::output::{Rezzy, fmt_duration};
+use crate::reference::Endpoint;
+use crate::network::Network;
+use crate::eth2::beacon_header;
+use crate::deposit::Withdrawal;
+
+// validators per /eth/v1/beacon/states/head/validators request, keeps the query string a sane length
+static ID_CHUNK: usize = 32;
+
+// an active validator starts with 32 ETH effective, anything lower has been penalized
+static MIN_EFFECTIVE_GWEI: u64 = 32_000_000_000;
+
+static FAR_FUTURE_EPOCH: u64 = u64::MAX;
+
+// a validator is named by its 0x prefixed 48 byte bls pubkey or its index
+pub fn parse_id(id: &str) -> Result<String> {
+    let id = id.trim().to_lowercase();
+    if id.chars().all(|c| c.is_ascii_digit()) && !id.is_empty() {
+        return Ok(id);
+    }
+    let key = id.trim_start_matches("0x");
+    if key.len() == 96 && key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(format!("0x{}", key));
+    }
+    Err(anyhow!("{} is neither a validator index nor a 48 byte pubkey", id))
+}
+
+// a 0x prefixed 20 byte execution address, lowercased for comparison
+pub fn parse_address(addr: &str) -> Result<String> {
+    let a = addr.trim().to_lowercase();
+    let hex = a.trim_start_matches("0x");
+    if hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(format!("0x{}", hex));
+    }
+    Err(anyhow!("{} is not a 20 byte execution address", addr))
+}
+
+// one pubkey or index per line, blank lines and # comments are skipped
+pub fn load_ids(path: &str) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)?;
+    contents.lines()
+        .map(|l| l.split('#').next().unwrap_or_default().trim())
+        .filter(|l| !l.is_empty())
+        .map(parse_id)
+        .collect()
+}
+
+pub fn gwei_to_eth(gwei: u64) -> String {
+    format!("{:.4} ETH", gwei as f64 / 1e9)
+}
+
+fn epoch_str(epoch: u64) -> String {
+    if epoch == FAR_FUTURE_EPOCH {
+        String::from("never")
+    } else {
+        epoch.to_string()
+    }
+}
+
+pub fn short_key(pubkey: &str) -> &str {
+    &pubkey[..pubkey.len().min(12)]
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidatorState {
+    pub index: u64,
+    pub pubkey: String,
+    pub status: String,
+    pub balance: u64,
+    pub effective_balance: u64,
+    pub slashed: bool,
+    pub activation_epoch: u64,
+    pub exit_epoch: u64,
+    pub withdrawal_credentials: String,
+}
+
+impl ValidatorState {
+    pub fn parse(j: &serde_json::Value) -> Option<ValidatorState> {
+        let v = &j["validator"];
+        let num = |n: &serde_json::Value| -> Option<u64> { n.as_str()?.parse().ok() };
+        Some(ValidatorState{
+            index: num(&j["index"])?,
+            pubkey: v["pubkey"].as_str()?.to_lowercase(),
+            status: String::from(j["status"].as_str()?),
+            balance: num(&j["balance"])?,
+            effective_balance: num(&v["effective_balance"])?,
+            slashed: v["slashed"].as_bool()?,
+            activation_epoch: num(&v["activation_epoch"])?,
+            exit_epoch: num(&v["exit_epoch"])?,
+            withdrawal_credentials: v["withdrawal_credentials"].as_str().unwrap_or_default().to_lowercase(),
+        })
+    }
+    pub fn name(&self) -> String {
+        format!("validator {}({})", self.index, short_key(self.pubkey.as_str()))
+    }
+    // whether the id it was asked for by names this validator
+    pub fn is(&self, id: &str) -> bool {
+        self.pubkey == id || self.index.to_string() == id
+    }
+}
+
+// state of every id the beacon node knows about, unknown ids are simply absent
+pub fn validator_states(node: &Endpoint, state_id: &str, ids: &[String]) -> Result<Vec<ValidatorState>> {
+    let mut states = Vec::new();
+    for chunk in ids.chunks(ID_CHUNK) {
+        let j = node.get(format!("/eth/v1/beacon/states/{}/validators?id={}", state_id, chunk.join(",")).as_str())?;
+        let data = j["data"].as_array().ok_or_else(|| anyhow!("malformed validators response"))?;
+        states.extend(data.iter().filter_map(ValidatorState::parse).filter(|v| chunk.iter().any(|id| v.is(id))));
+    }
+    Ok(states)
+}
+
+// pubkeys of `ids`, indices are looked up on the beacon node
+pub fn pubkeys(node: &Endpoint, ids: &[String]) -> Result<Vec<String>> {
+    let mut keys: Vec<String> = ids.iter().filter(|i| i.starts_with("0x")).map(|i| i.to_lowercase()).collect();
+    let indices: Vec<String> = ids.iter().filter(|i| !i.starts_with("0x")).cloned().collect();
+    if !indices.is_empty() {
+        keys.extend(validator_states(node, "head", &indices)?.into_iter().map(|v| v.pubkey));
+    }
+    keys.sort();
+    keys.dedup();
+    Ok(keys)
+}
+
+pub fn validator_status_check(eth2: &str, base_path: &str, ids: &[String]) {
+    let node = Endpoint::new(base_path);
+    let states = match validator_states(&node, "head", ids) {
+        Ok(s) => s,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get validator states from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+
+    // a validator can be listed by both its index and pubkey
+    let mut seen = Vec::new();
+    for id in ids {
+        let v = match states.iter().find(|v| v.is(id)) {
+            Some(v) if seen.contains(&v.index) => continue,
+            Some(v) => v,
+            None => {
+                let msg = Rezzy{ message: format!("validator {} is NOT known to {}: not deposited yet, or on another network", short_key(id), eth2) };
+                msg.write_red();
+                continue;
+            },
+        };
+        seen.push(v.index);
+        let summary = format!("{} {}, effective balance {}, activation epoch {}, exit epoch {}",
+            v.name(), v.status, gwei_to_eth(v.effective_balance), epoch_str(v.activation_epoch), epoch_str(v.exit_epoch));
+
+        if v.slashed {
+            let msg = Rezzy{ message: format!("{}: has been SLASHED", summary) };
+            msg.write_red();
+            continue;
+        }
+        match v.status.as_str() {
+            "active_ongoing" if v.effective_balance < MIN_EFFECTIVE_GWEI => {
+                let msg = Rezzy{ message: format!("{}: balance {} has been penalized below 32 ETH", summary, gwei_to_eth(v.balance)) };
+                msg.write_yellow();
+            },
+            "active_ongoing" => {
+                let msg = Rezzy{ message: summary };
+                msg.write_green();
+            },
+            "pending_initialized" | "pending_queued" => {
+                let msg = Rezzy{ message: format!("{}: waiting to be activated", summary) };
+                msg.write_yellow();
+            },
+            _ => {
+                // active_exiting, exited_*, withdrawal_*
+                let msg = Rezzy{ message: format!("{}: has EXITED or is exiting, check the exit was intended", summary) };
+                msg.write_red();
+            },
+        }
+    }
+}
+
+// per validator attestation rewards(gwei) for one epoch, a negative source or target means a missed attestation
+#[derive(Debug, PartialEq, Clone)]
+pub struct AttestationReward {
+    pub index: u64,
+    pub head: i64,
+    pub target: i64,
+    pub source: i64,
+    pub inactivity: i64,
+    // during an inactivity leak even a perfect attestation earns nothing
+    pub leak: bool,
+}
+
+impl AttestationReward {
+    pub fn parse(j: &serde_json::Value) -> Option<AttestationReward> {
+        let num = |n: &serde_json::Value| -> Option<i64> { n.as_str()?.parse().ok() };
+        Some(AttestationReward{
+            index: j["validator_index"].as_str()?.parse().ok()?,
+            head: num(&j["head"])?,
+            target: num(&j["target"])?,
+            source: num(&j["source"])?,
+            inactivity: num(&j["inactivity"]).unwrap_or(0),
+            leak: false,
+        })
+    }
+    // missing an attestation is penalized, a zero reward is a correct vote during a leak
+    pub fn missed(&self) -> bool {
+        self.source < 0 || self.target < 0
+    }
+    // included, but too late or on the wrong head to earn the head reward
+    pub fn late(&self) -> bool {
+        !self.missed() && !self.leak && self.head <= 0
+    }
+    pub fn total(&self) -> i64 {
+        self.head + self.target + self.source + self.inactivity
+    }
+}
+
+fn attestation_rewards(node: &Endpoint, epoch: u64, indices: &[String]) -> Result<Vec<AttestationReward>> {
+    let j = node.post(format!("/eth/v1/beacon/rewards/attestations/{}", epoch).as_str(), serde_json::json!(indices))?;
+    let total = j["data"]["total_rewards"].as_array().ok_or_else(|| anyhow!("malformed attestation rewards"))?;
+    // the ideal head reward is zero for every balance while the chain is leaking
+    let ideal = j["data"]["ideal_rewards"].as_array().cloned().unwrap_or_default();
+    let leak = !ideal.is_empty() && ideal.iter().all(|i| i["head"].as_str().and_then(|h| h.parse::<i64>().ok()).unwrap_or(0) <= 0);
+    Ok(total.iter().filter_map(AttestationReward::parse).map(|r| AttestationReward{ leak, ..r }).collect())
+}
+
+fn validator_balances(node: &Endpoint, state_id: &str, indices: &[String]) -> Result<Vec<(u64, u64)>> {
+    let mut balances = Vec::new();
+    for chunk in indices.chunks(ID_CHUNK) {
+        let j = node.get(format!("/eth/v1/beacon/states/{}/validator_balances?id={}", state_id, chunk.join(",")).as_str())?;
+        let data = j["data"].as_array().ok_or_else(|| anyhow!("malformed validator balances"))?;
+        balances.extend(data.iter().filter_map(|b| {
+            Some((b["index"].as_str()?.parse().ok()?, b["balance"].as_str()?.parse().ok()?))
+        }));
+    }
+    Ok(balances)
+}
+
+// is my validator actually earning: attestation rewards and balance change over the last `epochs` epochs
+pub fn validator_performance_check(eth2: &str, base_path: &str, ids: &[String], network: &Network, epochs: u64) {
+    let node = Endpoint::new(base_path);
+    let active: Vec<ValidatorState> = match validator_states(&node, "head", ids) {
+        Ok(s) => s.into_iter().filter(|v| v.status.starts_with("active")).collect(),
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get validator states from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+    if active.is_empty() {
+        return;
+    }
+    let current = match beacon_header(&node, "head") {
+        Ok((slot, _)) => slot / network.slots_per_epoch.max(1),
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get head slot from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+    let indices: Vec<String> = active.iter().map(|v| v.index.to_string()).collect();
+
+    // rewards are only final for epochs at least two behind the current one
+    let last = current.saturating_sub(2);
+    let first = last.saturating_sub(epochs.max(1) - 1);
+    let mut rewards = Vec::new();
+    for epoch in first..=last {
+        match attestation_rewards(&node, epoch, &indices) {
+            Ok(r) => rewards.extend(r),
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get attestation rewards for epoch {} from {}: {}", epoch, eth2, e) };
+                msg.write_yellow();
+            },
+        }
+    }
+
+    let start_slot = (first * network.slots_per_epoch).to_string();
+    let before = match validator_balances(&node, start_slot.as_str(), &indices) {
+        Ok(b) => b,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get balances at slot {} from {}(it may have pruned the state): {}", start_slot, eth2, e) };
+            msg.write_yellow();
+            Vec::new()
+        },
+    };
+
+    for v in &active {
+        let own: Vec<&AttestationReward> = rewards.iter().filter(|r| r.index == v.index).collect();
+        let missed = own.iter().filter(|r| r.missed()).count();
+        let late = own.iter().filter(|r| r.late()).count();
+        let earned: i64 = own.iter().map(|r| r.total()).sum();
+        // the balance also drops with every withdrawal sweep, so it is shown but not graded
+        let change = before.iter().find(|(i, _)| *i == v.index)
+            .map(|(_, b)| v.balance as i64 - *b as i64);
+
+        let trend = match change {
+            Some(c) if c < 0 => format!(", balance -{} since epoch {}(withdrawals included)", gwei_to_eth(c.unsigned_abs()), first),
+            Some(c) => format!(", balance +{} since epoch {}(withdrawals included)", gwei_to_eth(c as u64), first),
+            None => String::new(),
+        };
+        let summary = format!("{} missed {} and was late on {} of {} attestations(epochs {}-{}), earning {} gwei{}", v.name(), missed, late, own.len(), first, last, earned, trend);
+        if missed > 0 || earned < 0 {
+            let msg = Rezzy{ message: summary };
+            msg.write_red();
+        } else if late > 0 || own.is_empty() {
+            let msg = Rezzy{ message: summary };
+            msg.write_yellow();
+        } else {
+            let msg = Rezzy{ message: summary };
+            msg.write_green();
+        }
+    }
+}
+
+// first and last epoch of the sync committee period `epoch` falls in
+fn sync_period(network: &Network, epoch: u64) -> (u64, u64) {
+    let period = network.epochs_per_sync_committee_period.max(1);
+    let start = epoch / period * period;
+    (start, start + period - 1)
+}
+
+// seconds from now until `slot` starts, zero once it has
+fn until_slot(network: &Network, slot: u64, now: u64) -> u64 {
+    (network.genesis_time + slot * network.seconds_per_slot).saturating_sub(now)
+}
+
+fn proposer_duties(node: &Endpoint, epoch: u64) -> Result<Vec<(u64, u64)>> {
+    let j = node.get(format!("/eth/v1/validator/duties/proposer/{}", epoch).as_str())?;
+    let data = j["data"].as_array().ok_or_else(|| anyhow!("malformed proposer duties"))?;
+    Ok(data.iter().filter_map(|d| {
+        Some((d["validator_index"].as_str()?.parse().ok()?, d["slot"].as_str()?.parse().ok()?))
+    }).collect())
+}
+
+fn sync_duties(node: &Endpoint, epoch: u64, indices: &[String]) -> Result<Vec<u64>> {
+    let j = node.post(format!("/eth/v1/validator/duties/sync/{}", epoch).as_str(), serde_json::json!(indices))?;
+    let data = j["data"].as_array().ok_or_else(|| anyhow!("malformed sync committee duties"))?;
+    Ok(data.iter().filter_map(|d| d["validator_index"].as_str()?.parse().ok()).collect())
+}
+
+// beacon nodes only know proposers for the current and next epoch, so --duty-warn-epochs can't look further
+pub static MAX_DUTY_EPOCHS: u64 = 2;
+
+// the pre-maintenance check: is it safe to take these validators offline right now
+pub fn validator_duty_check(eth2: &str, base_path: &str, ids: &[String], network: &Network, warn_epochs: u64) {
+    let node = Endpoint::new(base_path);
+    let active: Vec<ValidatorState> = match validator_states(&node, "head", ids) {
+        Ok(s) => s.into_iter().filter(|v| v.status.starts_with("active")).collect(),
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get validator states from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+    if active.is_empty() {
+        return;
+    }
+    let current = match beacon_header(&node, "head") {
+        Ok((slot, _)) => slot / network.slots_per_epoch.max(1),
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get head slot from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+    let now = Utc::now().timestamp().max(0) as u64;
+
+    let mut proposals = Vec::new();
+    for epoch in current..current + MAX_DUTY_EPOCHS {
+        match proposer_duties(&node, epoch) {
+            Ok(d) => proposals.extend(d.into_iter().filter(|(i, _)| active.iter().any(|v| v.index == *i))),
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get proposer duties for epoch {} from {}: {}", epoch, eth2, e) };
+                msg.write_yellow();
+            },
+        }
+    }
+    let upcoming: Vec<&(u64, u64)> = proposals.iter().filter(|(_, slot)| until_slot(network, *slot, now) > 0).collect();
+    if upcoming.is_empty() {
+        let msg = Rezzy{ message: format!("No block proposals for {} validators in epochs {}-{}", active.len(), current, current + 1) };
+        msg.write_green();
+    }
+    for (index, slot) in upcoming {
+        let epoch = slot / network.slots_per_epoch.max(1);
+        let eta = fmt_duration(until_slot(network, *slot, now));
+        if epoch < current + warn_epochs {
+            let msg = Rezzy{ message: format!("validator {} PROPOSES at slot {} in {}, don't take it offline now", index, slot, eta) };
+            msg.write_red();
+        } else {
+            let msg = Rezzy{ message: format!("validator {} proposes at slot {} in {}", index, slot, eta) };
+            msg.write_yellow();
+        }
+    }
+
+    let indices: Vec<String> = active.iter().map(|v| v.index.to_string()).collect();
+    let (start, end) = sync_period(network, current);
+    let next = end + 1;
+    for (epoch, current_period) in [(current, true), (next, false)].iter() {
+        let members = match sync_duties(&node, *epoch, &indices) {
+            Ok(m) => m,
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get sync committee duties for epoch {} from {}: {}", epoch, eth2, e) };
+                msg.write_yellow();
+                continue;
+            },
+        };
+        for index in members {
+            if *current_period {
+                let left = fmt_duration(until_slot(network, next * network.slots_per_epoch, now));
+                let msg = Rezzy{ message: format!("validator {} is in the CURRENT sync committee(epochs {}-{}, {} left), downtime costs sync rewards", index, start, end, left) };
+                msg.write_yellow();
+            } else {
+                let msg = Rezzy{ message: format!("validator {} is in the next sync committee from epoch {}", index, next) };
+                msg.write_yellow();
+            }
+        }
+    }
+}
+
+// `allowed` are the execution addresses withdrawals may go to, any address when empty
+pub fn withdrawal_check(eth2: &str, base_path: &str, ids: &[String], allowed: &[String]) {
+    let node = Endpoint::new(base_path);
+    let states = match validator_states(&node, "head", ids) {
+        Ok(s) => s,
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to get validator withdrawal credentials from {}: {}", eth2, e) };
+            msg.write_red();
+            return;
+        },
+    };
+
+    let mut seen = Vec::new();
+    let mut bls = 0;
+    for v in states.iter().filter(|v| ids.iter().any(|id| v.is(id))) {
+        if seen.contains(&v.index) {
+            continue;
+        }
+        seen.push(v.index);
+        match Withdrawal::parse(v.withdrawal_credentials.as_str()) {
+            Ok(Withdrawal::Bls) => bls += 1,
+            Ok(w) => match withdrawal_problem(&w, allowed) {
+                Some(p) => {
+                    let msg = Rezzy{ message: format!("{} {}", v.name(), p) };
+                    msg.write_red();
+                },
+                None if allowed.is_empty() => println!("{} has {} withdrawal credentials, withdrawing to {}", v.name(), withdrawal_kind(&w), w.address().unwrap_or_default()),
+                None => {
+                    let msg = Rezzy{ message: format!("{} has {} withdrawal credentials, withdrawing to allowed address {}", v.name(), withdrawal_kind(&w), w.address().unwrap_or_default()) };
+                    msg.write_green();
+                },
+            },
+            Err(e) => {
+                let msg = Rezzy{ message: format!("{} has unrecognised withdrawal credentials {}: {}", v.name(), v.withdrawal_credentials, e) };
+                msg.write_red();
+            },
+        }
+    }
+    if bls > 0 {
+        let msg = Rezzy{ message: format!("{} validators have BLS(0x00) withdrawal credentials, submit a BLS to execution change", bls) };
+        msg.write_yellow();
+    }
+}
+
+fn withdrawal_kind(w: &Withdrawal) -> &'static str {
+    match w {
+        Withdrawal::Bls => "BLS(0x00)",
+        Withdrawal::Execution(_) => "execution(0x01)",
+        Withdrawal::Compounding(_) => "compounding(0x02)",
+    }
+}
+
+// why withdrawals to `w` are unsafe given the `allowed` addresses, None when they're fine or there's no allowlist
+fn withdrawal_problem(w: &Withdrawal, allowed: &[String]) -> Option<String> {
+    let addr = w.address()?;
+    if allowed.is_empty() || allowed.iter().any(|a| a == addr) {
+        return None;
+    }
+    Some(format!("withdraws to {}, which is NOT in withdrawal_addresses: verify who controls it, credentials can't be changed", addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validator_ids_and_state() {
+        let key = format!("0x{}", "AB".repeat(48));
+        assert_eq!(parse_id(key.as_str()).unwrap(), key.to_lowercase());
+        assert_eq!(parse_id(" 12345 ").unwrap(), "12345");
+        assert!(parse_id("0x1234").is_err());
+
+        let v = ValidatorState::parse(&json!({
+            "index": "12345",
+            "balance": "32001000000",
+            "status": "active_ongoing",
+            "validator": {
+                "pubkey": key,
+                "withdrawal_credentials": "0x010000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "effective_balance": "32000000000",
+                "slashed": false,
+                "activation_eligibility_epoch": "10",
+                "activation_epoch": "20",
+                "exit_epoch": "18446744073709551615",
+                "withdrawable_epoch": "18446744073709551615",
+            },
+        })).unwrap();
+        assert!(v.is("12345") && v.is(key.to_lowercase().as_str()));
+        assert_eq!(epoch_str(v.exit_epoch), "never");
+        assert_eq!(gwei_to_eth(v.balance), "32.0010 ETH");
+    }
+
+    #[test]
+    fn missed_and_late_attestations() {
+        let reward = |head: &str, target: &str, source: &str| AttestationReward::parse(&json!({
+            "validator_index": "7", "head": head, "target": target, "source": source, "inactivity": "0",
+        })).unwrap();
+        let ok = reward("2000", "4000", "2000");
+        assert!(!ok.missed() && !ok.late());
+        let late = reward("0", "4000", "2000");
+        assert!(!late.missed() && late.late());
+        let missed = reward("0", "-4000", "-2000");
+        assert!(missed.missed() && !missed.late());
+        assert_eq!(missed.total(), -6000);
+
+        // nothing is earned for a correct vote during an inactivity leak
+        let leaking = AttestationReward{ leak: true, ..reward("0", "0", "0") };
+        assert!(!leaking.missed() && !leaking.late());
+    }
+
+    #[test]
+    fn sync_period_and_slot_timing() {
+        let mut net = Network::mainnet();
+        assert_eq!(sync_period(&net, 0), (0, 255));
+        assert_eq!(sync_period(&net, 300), (256, 511));
+        net.epochs_per_sync_committee_period = 8;
+        assert_eq!(sync_period(&net, 20), (16, 23));
+
+        assert_eq!(until_slot(&net, 10, net.genesis_time), 120);
+        assert_eq!(until_slot(&net, 10, net.genesis_time + 500), 0);
+    }
+
+    #[test]
+    fn withdrawal_problems() {
+        let allowed = vec![parse_address(&format!("0x{}", "AA".repeat(20))).unwrap()];
+        assert_eq!(allowed[0], format!("0x{}", "aa".repeat(20)));
+        assert!(parse_address("0x1234").is_err());
+
+        let execution = Withdrawal::parse(&format!("01{}{}", "00".repeat(11), "aa".repeat(20))).unwrap();
+        let compounding = Withdrawal::parse(&format!("02{}{}", "00".repeat(11), "bb".repeat(20))).unwrap();
+        assert!(withdrawal_problem(&execution, &allowed).is_none());
+        assert!(withdrawal_problem(&compounding, &[]).is_none());
+        assert!(withdrawal_problem(&compounding, &allowed).unwrap().contains("NOT in withdrawal_addresses"));
+        assert!(withdrawal_problem(&Withdrawal::Bls, &allowed).is_none());
+        assert_eq!(withdrawal_kind(&compounding), "compounding(0x02)");
+    }
+}
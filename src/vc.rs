@@ -0,0 +1,414 @@
+use std::fs;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use crate::output::Rezzy;
+use crate::reference::Endpoint;
+use crate::docker;
+use crate::eth2::{node_health, Health};
+use crate::validator::{parse_address, short_key};
+
+// flags validator clients are pointed at their beacon node(s) with
+static BEACON_FLAGS: [&str; 8] = ["--beacon-nodes", "--beacon-node", "--beacon-node-api-endpoint", "--beacon-node-api-endpoints",
+    "--beaconNodes", "--server", "--beacon-rest-api-provider", "--beacon-rpc-provider"];
+
+// flags naming the keymanager api bearer token file
+static TOKEN_FLAGS: [&str; 3] = ["--http-token-path", "--keymanager-token-file", "--keymanager.tokenFile"];
+
+// flags setting the fee recipient for keys without their own(lighthouse/prysm/nimbus, teku, lodestar)
+static FEE_RECIPIENT_FLAGS: [&str; 3] = ["--suggested-fee-recipient", "--validators-proposer-default-fee-recipient", "--suggestedFeeRecipient"];
+
+static ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+static VALIDATOR_CLIENTS: [&str; 5] = ["LIGHTHOUSE", "PRYSM", "TEKU", "NIMBUS", "LODESTAR"];
+
+pub fn parse_client(name: &str) -> Option<String> {
+    let name = name.to_uppercase();
+    if VALIDATOR_CLIENTS.contains(&name.as_str()) {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+// process keywords, and the subcommands that tell a validator client from the same client's beacon node
+fn vc_keywords(client: &str) -> (&'static [&'static str], &'static [&'static str]) {
+    match client {
+        "LIGHTHOUSE" => (&["lighthouse"], &["vc", "validator_client", "validator"]),
+        "PRYSM" => (&["validator", "prysm"], &[]),
+        "TEKU" => (&["teku"], &["vc", "validator-client"]),
+        "NIMBUS" => (&["nimbus_validator_client"], &[]),
+        "LODESTAR" => (&["lodestar"], &["validator"]),
+        _ => (&[], &[]),
+    }
+}
+
+// is `cmd` this client's validator client, and not another client's that happens to match its keywords
+fn is_vc(client: &str, cmd: &[String]) -> bool {
+    let exe = cmd.first().map(|c| c.rsplit('/').next().unwrap_or_default()).unwrap_or_default();
+    let (_, subcommands) = vc_keywords(client);
+    let matches = match client {
+        // prysm's validator binary doesn't carry the client's name, so only the executable(or prysm.sh) identifies it
+        "PRYSM" => exe.starts_with("validator")
+            || (cmd.iter().any(|c| c.ends_with("prysm.sh")) && cmd.iter().any(|c| c == "validator")),
+        _ => subcommands.is_empty()
+            || subcommands.contains(&exe)
+            || cmd.iter().skip(1).any(|c| subcommands.contains(&c.as_str())),
+    };
+    let line = cmd.join(" ").to_lowercase();
+    matches && !VALIDATOR_CLIENTS.iter()
+        .filter(|c| **c != client)
+        .any(|c| line.contains(c.to_lowercase().as_str()))
+}
+
+pub fn vc_processes(client: &str) -> Vec<(i32, Vec<String>)> {
+    let (keys, _) = vc_keywords(client);
+    docker::processes_matching(keys).into_iter()
+        .filter(|(_, cmd)| is_vc(client, cmd))
+        .collect()
+}
+
+fn default_beacon_node(client: &str) -> &'static str {
+    match client {
+        "PRYSM" => "127.0.0.1:4000",
+        "TEKU" => "http://127.0.0.1:5051",
+        "LODESTAR" => "http://127.0.0.1:9596",
+        _ => "http://127.0.0.1:5052",
+    }
+}
+
+pub fn default_keymanager(client: &str) -> String {
+    match client {
+        "PRYSM" => String::from("http://127.0.0.1:7500"),
+        "TEKU" | "NIMBUS" => String::from("http://127.0.0.1:5052"),
+        _ => String::from("http://127.0.0.1:5062"),
+    }
+}
+
+// value of a boolean flag: bare `--flag` is true, `--flag=false`/`--flag false` say otherwise
+pub fn bool_flag(cmd: &[String], flag: &str) -> Option<bool> {
+    let pos = cmd.iter().position(|c| c == flag || c.starts_with(&format!("{}=", flag)))?;
+    let val = match cmd[pos].split_once('=') {
+        Some((_, v)) => Some(v),
+        None => cmd.get(pos + 1).map(|v| v.as_str()).filter(|v| *v == "true" || *v == "false"),
+    };
+    Some(val != Some("false"))
+}
+
+// flag turning doppelganger protection on, and whether the client has it on by default
+fn doppelganger_flag(client: &str) -> (&'static str, bool) {
+    match client {
+        "LIGHTHOUSE" => ("--enable-doppelganger-protection", false),
+        "PRYSM" => ("--enable-doppelganger", false),
+        "TEKU" => ("--doppelganger-detection-enabled", false),
+        "NIMBUS" => ("--doppelganger-detection", true),
+        _ => ("--doppelgangerProtection", false),
+    }
+}
+
+// `name` is how the validator client is reported, e.g. with the inventory host it runs on
+pub fn doppelganger_check(name: &str, client: &str, cmd: &[String]) {
+    let (flag, default) = doppelganger_flag(client);
+    if bool_flag(cmd, flag).unwrap_or(default) {
+        let msg = Rezzy{ message: format!("{} validator client has doppelganger protection enabled", name) };
+        msg.write_green();
+    } else {
+        let msg = Rezzy{ message: format!("{} validator client has doppelganger protection DISABLED, enable it with {} when moving keys", name, flag) };
+        msg.write_yellow();
+    }
+}
+
+// bearer token from the --keymanager-token file, a token flag on the command line, or the client's default location
+fn token_path(client: &str, token: Option<&String>, cmd: &[String]) -> Option<String> {
+    if let Some(t) = token {
+        return Some(t.clone());
+    }
+    if let Some(t) = docker::flag_value(cmd, &TOKEN_FLAGS) {
+        return Some(t);
+    }
+    match client {
+        "LIGHTHOUSE" => docker::flag_value(cmd, &["--datadir", "-d"])
+            .map(|d| format!("{}/validators/api-token.txt", d.trim_end_matches('/'))),
+        "PRYSM" => docker::flag_value(cmd, &["--wallet-dir"])
+            .map(|d| format!("{}/auth-token", d.trim_end_matches('/'))),
+        _ => None,
+    }
+}
+
+// the token is the last line, prysm puts a secret ahead of it
+pub fn read_token(path: &str) -> Result<String> {
+    let contents = fs::read_to_string(path)?;
+    contents.lines()
+        .map(|l| l.trim())
+        .rfind(|l| !l.is_empty())
+        .map(String::from)
+        .ok_or_else(|| anyhow!("{} is empty", path))
+}
+
+fn beacon_node_check(client: &str, bn: &str) {
+    if bn.starts_with("http") {
+        match node_health(&Endpoint::new(bn)) {
+            Ok(Health::Ready) => {
+                let msg = Rezzy{ message: format!("{} validator client beacon node {} is healthy", client, bn) };
+                msg.write_green();
+            },
+            Ok(h) => {
+                let msg = Rezzy{ message: format!("{} validator client beacon node {} is NOT ready({:?})", client, bn, h) };
+                msg.write_red();
+            },
+            Err(e) => {
+                let msg = Rezzy{ message: format!("{} validator client beacon node {} is UNREACHABLE: {}", client, bn, e) };
+                msg.write_red();
+            },
+        }
+        return;
+    }
+    // prysm talks grpc to its beacon node, being able to connect is all valid8r can check
+    let reachable = bn.to_socket_addrs().ok()
+        .and_then(|mut a| a.next())
+        .map(|a| TcpStream::connect_timeout(&a, Duration::from_secs(3)).is_ok())
+        .unwrap_or(false);
+    if reachable {
+        let msg = Rezzy{ message: format!("{} validator client beacon node {} is reachable", client, bn) };
+        msg.write_green();
+    } else {
+        let msg = Rezzy{ message: format!("{} validator client beacon node {} is UNREACHABLE", client, bn) };
+        msg.write_red();
+    }
+}
+
+// pubkeys loaded in a validator client, local keystores and remote(web3signer) keys
+pub fn loaded_keys(keymanager: &Endpoint) -> Result<Vec<String>> {
+    let j = keymanager.get("/eth/v1/keystores")?;
+    let mut keys: Vec<String> = j["data"].as_array()
+        .ok_or_else(|| anyhow!("malformed keystores response"))?
+        .iter()
+        .filter_map(|k| k["validating_pubkey"].as_str().map(|p| p.to_lowercase()))
+        .collect();
+    if let Ok(r) = keymanager.get("/eth/v1/remotekeys") {
+        if let Some(remote) = r["data"].as_array() {
+            keys.extend(remote.iter().filter_map(|k| k["pubkey"].as_str().map(|p| p.to_lowercase())));
+        }
+    }
+    Ok(keys)
+}
+
+// keymanager endpoint with the bearer token attached, when one can be found
+pub fn keymanager(client: &str, url: &str, token: Option<&String>, cmd: &[String]) -> Endpoint {
+    let mut km = Endpoint::new(url);
+    match token_path(client, token, cmd) {
+        Some(path) => match read_token(path.as_str()) {
+            Ok(t) => {
+                km.headers.insert(String::from("Authorization"), format!("Bearer {}", t));
+            },
+            Err(e) => {
+                let msg = Rezzy{ message: format!("{} keymanager token {} can't be read: {}", client, path, e) };
+                msg.write_red();
+            },
+        },
+        None => {
+            let msg = Rezzy{ message: format!("Could not find the {} keymanager token(use --keymanager-token)", client) };
+            msg.write_yellow();
+        },
+    }
+    km
+}
+
+// what's wrong with a fee recipient, if anything
+fn fee_recipient_problem(addr: Option<&str>, expected: Option<&String>) -> Option<String> {
+    match (addr, expected) {
+        (None, _) => Some(String::from("has NO fee recipient set")),
+        (Some(a), _) if a == ZERO_ADDRESS => Some(String::from("has the ZERO address as fee recipient, execution rewards are burned")),
+        (Some(a), Some(e)) if a != e => Some(format!("fee recipient {} does NOT match the configured {}", a, e)),
+        _ => None,
+    }
+}
+
+// the validator client's default fee recipient flag, checked whether or not the keymanager answers
+fn default_fee_recipient(client: &str, cmd: &[String], expected: Option<&String>) -> Option<String> {
+    match docker::flag_value(cmd, &FEE_RECIPIENT_FLAGS).as_deref().map(parse_address) {
+        Some(Ok(a)) => {
+            match fee_recipient_problem(Some(a.as_str()), expected) {
+                Some(p) => {
+                    let msg = Rezzy{ message: format!("{} default {}", client, p) };
+                    msg.write_red();
+                },
+                None => {
+                    let msg = Rezzy{ message: format!("{} default fee recipient is {}", client, a) };
+                    msg.write_green();
+                },
+            }
+            Some(a)
+        },
+        Some(Err(e)) => {
+            let msg = Rezzy{ message: format!("{} default fee recipient is invalid: {}", client, e) };
+            msg.write_red();
+            None
+        },
+        None => {
+            if !cmd.is_empty() {
+                let msg = Rezzy{ message: format!("{} has no default fee recipient flag({}), keys without their own use the beacon node's", client, FEE_RECIPIENT_FLAGS.join("/")) };
+                msg.write_yellow();
+            }
+            None
+        },
+    }
+}
+
+// the fee recipient of every loaded key from the keymanager api, keys it has none for(404) use `default`
+fn fee_recipient_check(km: &Endpoint, keys: &[String], default: Option<&String>, expected: Option<&String>) {
+    let mut ok: Vec<String> = Vec::new();
+    let mut good = 0;
+    for k in keys {
+        let addr = match km.get_optional(format!("/eth/v1/validator/{}/feerecipient", k).as_str()) {
+            Ok(Some(j)) => match j["data"]["ethaddress"].as_str().map(parse_address) {
+                Some(Ok(a)) => Some(a),
+                Some(Err(e)) => {
+                    let msg = Rezzy{ message: format!("validator {} fee recipient from the keymanager is invalid: {}", short_key(k), e) };
+                    msg.write_red();
+                    continue;
+                },
+                None => {
+                    let msg = Rezzy{ message: format!("validator {} fee recipient response from the keymanager has no ethaddress", short_key(k)) };
+                    msg.write_yellow();
+                    continue;
+                },
+            },
+            Ok(None) => default.cloned(),
+            Err(e) => {
+                let msg = Rezzy{ message: format!("Unable to get the fee recipient of validator {} from the keymanager: {}", short_key(k), e) };
+                msg.write_yellow();
+                continue;
+            },
+        };
+        match fee_recipient_problem(addr.as_deref(), expected) {
+            Some(p) => {
+                let msg = Rezzy{ message: format!("validator {} {}", short_key(k), p) };
+                msg.write_red();
+            },
+            None => {
+                good += 1;
+                ok.extend(addr);
+            },
+        }
+    }
+    ok.sort();
+    ok.dedup();
+    if good > 0 {
+        let msg = Rezzy{ message: format!("{} of {} validators have fee recipient {}", good, keys.len(), ok.join(", ")) };
+        msg.write_green();
+    }
+}
+
+// process, beacon node connections and the keys the validator client has loaded, which are returned
+pub fn vc_check(client: &str, keymanager_url: Option<&String>, token: Option<&String>, fee_recipient: Option<&String>) -> Option<Vec<String>> {
+    let procs = vc_processes(client);
+    let cmd: Vec<String> = procs.first().map(|(_, c)| c.clone()).unwrap_or_default();
+    match procs.first() {
+        Some((pid, _)) => {
+            let msg = Rezzy{ message: format!("{} validator client is running(pid {})", client, pid) };
+            msg.write_green();
+        },
+        None => {
+            let msg = Rezzy{ message: format!("{} validator client is NOT running", client) };
+            msg.write_red();
+        },
+    }
+    if !procs.is_empty() {
+        doppelganger_check(client, client, &cmd);
+    }
+    if procs.len() > 1 {
+        let msg = Rezzy{ message: format!("{} {} validator client processes are running, make sure they don't share keys", procs.len(), client) };
+        msg.write_yellow();
+    }
+
+    let beacon_nodes = docker::flag_value(&cmd, &BEACON_FLAGS)
+        .unwrap_or_else(|| String::from(default_beacon_node(client)));
+    for bn in beacon_nodes.split(',').map(|b| b.trim()).filter(|b| !b.is_empty()) {
+        beacon_node_check(client, bn);
+    }
+
+    let default = default_fee_recipient(client, &cmd, fee_recipient);
+    let url = keymanager_url.cloned().unwrap_or_else(|| default_keymanager(client));
+    let km = keymanager(client, url.as_str(), token, &cmd);
+    match loaded_keys(&km) {
+        Ok(keys) => {
+            let msg = Rezzy{ message: format!("{} validator client has {} keys loaded(keymanager {})", client, keys.len(), url) };
+            if keys.is_empty() {
+                msg.write_yellow();
+            } else {
+                msg.write_green();
+            }
+            fee_recipient_check(&km, &keys, default.as_ref(), fee_recipient);
+            Some(keys)
+        },
+        Err(e) => {
+            let msg = Rezzy{ message: format!("Unable to list keys from the {} keymanager API at {}: {}", client, url, e) };
+            msg.write_red();
+            None
+        },
+    }
+}
+
+// the loaded keys must be exactly the validators valid8r was told about
+pub fn keyset_check(client: &str, loaded: &[String], expected: &[String]) {
+    let missing: Vec<&String> = expected.iter().filter(|k| !loaded.contains(k)).collect();
+    let extra: Vec<&String> = loaded.iter().filter(|k| !expected.contains(k)).collect();
+    for k in &missing {
+        let msg = Rezzy{ message: format!("validator {} is NOT loaded in {}", short_key(k), client) };
+        msg.write_red();
+    }
+    for k in &extra {
+        let msg = Rezzy{ message: format!("{} has unexpected validator {} loaded", client, short_key(k)) };
+        msg.write_yellow();
+    }
+    if missing.is_empty() && extra.is_empty() {
+        let msg = Rezzy{ message: format!("{} has exactly the {} expected validators loaded", client, expected.len()) };
+        msg.write_green();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validator_client_token_paths() {
+        let cmd: Vec<String> = ["lighthouse", "vc", "--datadir", "/data/lh/", "--beacon-nodes", "http://bn1:5052,http://bn2:5052"]
+            .iter().map(|s| String::from(*s)).collect();
+        assert_eq!(token_path("LIGHTHOUSE", None, &cmd).unwrap(), "/data/lh/validators/api-token.txt");
+        assert_eq!(docker::flag_value(&cmd, &BEACON_FLAGS).unwrap(), "http://bn1:5052,http://bn2:5052");
+
+        let cmd: Vec<String> = ["node", "lodestar", "validator", "--keymanager.tokenFile=/secrets/km"]
+            .iter().map(|s| String::from(*s)).collect();
+        assert_eq!(token_path("LODESTAR", None, &cmd).unwrap(), "/secrets/km");
+        assert_eq!(parse_client("lodestar").unwrap(), "LODESTAR");
+        assert!(parse_client("geth").is_none());
+
+        // lodestar's validator runs under node with a `validator` subcommand, it isn't prysm's
+        assert!(is_vc("LODESTAR", &cmd) && !is_vc("PRYSM", &cmd));
+        let prysm = |args: &[&str]| -> Vec<String> { args.iter().map(|s| String::from(*s)).collect() };
+        assert!(is_vc("PRYSM", &prysm(&["/usr/local/bin/validator", "--wallet-dir", "/data/wallet"])));
+        assert!(is_vc("PRYSM", &prysm(&["/bin/bash", "./prysm.sh", "validator", "--mainnet"])));
+        assert!(!is_vc("PRYSM", &prysm(&["/bin/bash", "./prysm.sh", "beacon-chain"])));
+    }
+
+    #[test]
+    fn doppelganger_flags() {
+        let cmd: Vec<String> = ["nimbus_validator_client", "--doppelganger-detection=false", "--enable-doppelganger", "--x"]
+            .iter().map(|s| String::from(*s)).collect();
+        assert_eq!(bool_flag(&cmd, "--doppelganger-detection"), Some(false));
+        assert_eq!(bool_flag(&cmd, "--enable-doppelganger"), Some(true));
+        assert_eq!(bool_flag(&cmd, "--doppelgangerProtection"), None);
+    }
+
+    #[test]
+    fn fee_recipient_problems() {
+        let expected = format!("0x{}", "ab".repeat(20));
+        assert!(fee_recipient_problem(Some(expected.as_str()), Some(&expected)).is_none());
+        assert!(fee_recipient_problem(Some(ZERO_ADDRESS), None).unwrap().contains("ZERO"));
+        assert!(fee_recipient_problem(None, Some(&expected)).unwrap().contains("NO fee recipient"));
+        assert!(fee_recipient_problem(Some(&format!("0x{}", "cd".repeat(20))), Some(&expected)).unwrap().contains("does NOT match"));
+        assert!(fee_recipient_problem(Some(&format!("0x{}", "cd".repeat(20))), None).is_none());
+    }
+}